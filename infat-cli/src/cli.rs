@@ -1,5 +1,5 @@
-use clap::{Parser, Subcommand};
-use infat_lib::GlobalOptions;
+use clap::{Args, Parser, Subcommand};
+use infat_lib::{GlobalOptions, agent::DEFAULT_LABEL, capability::Enforcement};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug, Clone)]
@@ -30,27 +30,44 @@ pub(crate) struct Cli {
     /// Continue processing on errors when possible
     #[arg(long)]
     robust: bool,
+
+    /// Rebuild the cached application index before running
+    #[arg(long, global = true)]
+    refresh_apps: bool,
 }
 
 #[derive(Subcommand, Debug, Clone)]
 pub(crate) enum Commands {
+    /// Apply the configuration, as running infat without a subcommand does
+    Apply {
+        #[command(flatten)]
+        enforcement: EnforcementArgs,
+    },
+
     /// Show file association information
     Info {
         /// Show information for a specific application
-        #[arg(long, conflicts_with_all = ["ext", "scheme", "type"])]
+        #[arg(long, conflicts_with_all = ["ext", "scheme", "type", "mime"])]
         app: Option<String>,
 
         /// Show information for a file extension
-        #[arg(long, conflicts_with_all = ["app", "scheme", "type"])]
+        #[arg(long, conflicts_with_all = ["app", "scheme", "type", "mime"])]
         ext: Option<String>,
 
         /// Show information for a URL scheme
-        #[arg(long, conflicts_with_all = ["app", "ext", "type"])]
+        #[arg(long, conflicts_with_all = ["app", "ext", "type", "mime"])]
         scheme: Option<String>,
 
         /// Show information for a file type
-        #[arg(long, conflicts_with_all = ["app", "ext", "scheme"])]
+        #[arg(long, conflicts_with_all = ["app", "ext", "scheme", "mime"])]
         r#type: Option<String>,
+
+        /// Show information for a MIME type
+        #[arg(long, conflicts_with_all = ["app", "ext", "scheme", "type"])]
+        mime: Option<String>,
+
+        #[command(flatten)]
+        view: AppView,
     },
 
     /// Set default application for file extension, URL scheme, or file type
@@ -58,7 +75,7 @@ pub(crate) enum Commands {
         /// Application name to set as default
         app_name: String,
 
-        /// File extension to associate (without the dot)
+        /// File extension to associate, e.g. `md` or `tar.gz`
         #[arg(long, conflicts_with_all = ["scheme", "type"])]
         ext: Option<String>,
 
@@ -66,9 +83,74 @@ pub(crate) enum Commands {
         #[arg(long, conflicts_with_all = ["ext", "type"])]
         scheme: Option<String>,
 
-        /// File type to associate
+        /// File type to associate (a supertype, a `[supertypes]` name from the config, or a UTI)
         #[arg(long, conflicts_with_all = ["ext", "scheme"])]
         r#type: Option<String>,
+
+        /// Also set every type conforming to `--type`, except those a more
+        /// specific `[types]` entry in the config claims
+        #[arg(long, requires = "type")]
+        recursive: bool,
+
+        #[command(flatten)]
+        enforcement: EnforcementArgs,
+    },
+
+    /// Compare the configuration with the current handlers and report drift
+    ///
+    /// Exits with 0 when everything matches, 2 when an entry has drifted, 3 when an
+    /// entry could not be checked, and 1 on errors
+    Status {
+        /// Emit the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Validate the configuration without applying it
+    ///
+    /// Reports unknown supertypes, UTIs, schemes and apps with suggestions for
+    /// likely typos. Exits with 0 when every entry can be applied and 1 otherwise
+    Check {
+        /// Emit the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Keep running and re-apply the configuration whenever an association drifts
+    ///
+    /// Watches the configuration file and the Launch Services database. Entries
+    /// marked `enforce = false` are applied once and then left alone.
+    Watch {
+        /// How long to wait for changes to settle before acting, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 500)]
+        debounce: u64,
+
+        /// How often to check the watched files, in milliseconds
+        #[arg(long, value_name = "MS", default_value_t = 1000)]
+        interval: u64,
+
+        #[command(flatten)]
+        enforcement: EnforcementArgs,
+    },
+
+    /// Manage a LaunchAgent that applies the configuration at login
+    Agent {
+        #[command(subcommand)]
+        action: AgentAction,
+    },
+
+    /// List the supertypes accepted by `--type` and the `[types]` table
+    Types {
+        /// Only show supertypes whose name, alias or UTI contains this text
+        query: Option<String>,
+
+        /// Only show one category, e.g. `image` or `source-code`
+        #[arg(long)]
+        category: Option<String>,
+
+        /// Emit the catalogue as JSON
+        #[arg(long)]
+        json: bool,
     },
 
     /// Initialize configuration from current Launch Services settings
@@ -79,6 +161,74 @@ pub(crate) enum Commands {
     },
 }
 
+#[derive(Subcommand, Debug, Clone)]
+pub(crate) enum AgentAction {
+    /// Write the LaunchAgent to ~/Library/LaunchAgents and load it
+    Install(AgentArgs),
+
+    /// Unload the LaunchAgent and remove it
+    Uninstall {
+        /// Label of the agent to remove
+        #[arg(long, default_value = DEFAULT_LABEL)]
+        label: String,
+    },
+
+    /// Print the LaunchAgent property list without installing it
+    Print(AgentArgs),
+}
+
+/// Extra sections for `info --app`
+#[derive(Args, Debug, Clone)]
+pub(crate) struct AppView {
+    /// Also show the app's name in this language, e.g. `de` or `pt-BR`
+    #[arg(long, requires = "app")]
+    pub(crate) lang: Option<String>,
+
+    /// Show everything the app is currently the default for, and how that
+    /// differs from what it declares
+    #[arg(long, requires = "app")]
+    pub(crate) owned: bool,
+}
+
+/// How to treat an app that doesn't declare what it's set for
+#[derive(Args, Debug, Clone)]
+pub(crate) struct EnforcementArgs {
+    /// Fail instead of warning when an app doesn't declare the type or scheme it's set for
+    #[arg(long, conflicts_with = "force")]
+    strict: bool,
+
+    /// Set apps without checking which types and schemes they declare
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct AgentArgs {
+    /// launchd label, also used as the plist file name
+    #[arg(long, default_value = DEFAULT_LABEL)]
+    pub(crate) label: String,
+
+    /// Also re-apply every N seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub(crate) interval: Option<u64>,
+
+    /// Also re-apply whenever the configuration file changes
+    #[arg(long)]
+    pub(crate) watch_config: bool,
+
+    /// File to write standard output to
+    #[arg(long, value_name = "PATH")]
+    pub(crate) stdout: Option<PathBuf>,
+
+    /// File to write standard error to
+    #[arg(long, value_name = "PATH")]
+    pub(crate) stderr: Option<PathBuf>,
+
+    /// infat executable to run (defaults to this one)
+    #[arg(long, value_name = "PATH")]
+    pub(crate) program: Option<PathBuf>,
+}
+
 impl From<&Cli> for GlobalOptions {
     fn from(cli: &Cli) -> Self {
         Self {
@@ -86,6 +236,55 @@ impl From<&Cli> for GlobalOptions {
             verbose: cli.verbose,
             quiet: cli.quiet,
             robust: cli.robust,
+            refresh_apps: cli.refresh_apps,
         }
     }
 }
+
+impl From<&EnforcementArgs> for Enforcement {
+    fn from(args: &EnforcementArgs) -> Self {
+        if args.force {
+            Enforcement::Off
+        } else if args.strict {
+            Enforcement::Strict
+        } else {
+            Enforcement::Warn
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("infat").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn enforcement_flags_belong_to_the_commands_that_set_apps() {
+        let enforcement = |args: &[&str]| match parse(args).unwrap().command {
+            Some(
+                Commands::Apply { enforcement }
+                | Commands::Set { enforcement, .. }
+                | Commands::Watch { enforcement, .. },
+            ) => Enforcement::from(&enforcement),
+            command => panic!("unexpected command {command:?}"),
+        };
+
+        assert_eq!(enforcement(&["apply"]), Enforcement::Warn);
+        assert_eq!(enforcement(&["apply", "--strict"]), Enforcement::Strict);
+        assert_eq!(
+            enforcement(&["set", "Foo", "--ext", "md", "--force"]),
+            Enforcement::Off
+        );
+        assert_eq!(enforcement(&["watch", "--strict"]), Enforcement::Strict);
+
+        // Not before the subcommand, where they would be ignored, nor for
+        // commands that don't set anything
+        assert!(parse(&["--strict", "set", "Foo", "--ext", "md"]).is_err());
+        assert!(parse(&["--force"]).is_err());
+        assert!(parse(&["status", "--force"]).is_err());
+        assert!(parse(&["apply", "--strict", "--force"]).is_err());
+    }
+}
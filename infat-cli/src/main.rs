@@ -3,17 +3,31 @@ use color_eyre::{
     eyre::{Context, Result},
     owo_colors::OwoColorize,
 };
-use infat_lib::{GlobalOptions, app, association, config, macos::launch_services_db};
+use infat_lib::{
+    GlobalOptions, agent, app, app_index, association,
+    bundle::HandlerRank,
+    capability::{Coverage, Enforcement},
+    check::{self, Severity},
+    config,
+    config::EntryKind,
+    localization,
+    macos::launch_services_db,
+    status::{self, EntryState},
+    uti::{Category, DynamicUti, SuperType, TagClass, TypeDeclaration, UtiGraph},
+    watch,
+};
 use nerdicons_rs::icons::md::{
     RSCHART_BAR, RSCHECK, RSCONTENT_SAVE_MOVE_OUTLINE, RSFILE_DOCUMENT, RSFILE_SEARCH, RSLINK,
     RSTAG,
 };
-use std::path::PathBuf;
-use tracing::info;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
 
 mod cli;
 
-use cli::{Cli, Commands};
+use cli::{AgentAction, AgentArgs, AppView, Cli, Commands};
 
 fn main() -> Result<()> {
     // Color eyre for them goooood errors
@@ -25,30 +39,83 @@ fn main() -> Result<()> {
     // Initialize tracing
     infat_lib::init_tracing(&global_opts).wrap_err("Failed to initialize logging")?;
 
+    if global_opts.refresh_apps {
+        app_index::clear_cache().wrap_err("Failed to clear the application index")?;
+    }
+
     // Handle commands
     match cli.command {
         None => {
             // No subcommand provided - load and apply configuration
             // Kind of bespoke behavior but infat stands for infatuate
             // I like to think it's just running the verb
-            handle_config_load(&global_opts).wrap_err("Failed to load and apply configuration")?;
+            handle_config_load(&global_opts, Enforcement::default())
+                .wrap_err("Failed to load and apply configuration")?;
+        }
+        Some(Commands::Apply { enforcement }) => {
+            handle_config_load(&global_opts, (&enforcement).into())
+                .wrap_err("Failed to load and apply configuration")?;
         }
         Some(Commands::Info {
             app,
             ext,
             scheme,
             r#type,
+            mime,
+            view,
         }) => {
-            handle_info_command(app, ext, scheme, r#type).wrap_err("Info command failed")?;
+            handle_info_command(&global_opts, app, ext, scheme, r#type, mime, view)
+                .wrap_err("Info command failed")?;
         }
         Some(Commands::Set {
             app_name,
             ext,
             scheme,
             r#type,
+            recursive,
+            enforcement,
+        }) => {
+            handle_set_command(
+                &global_opts,
+                app_name,
+                ext,
+                scheme,
+                r#type,
+                recursive,
+                (&enforcement).into(),
+            )
+            .wrap_err("Set command failed")?;
+        }
+        Some(Commands::Status { json }) => {
+            let code =
+                handle_status_command(&global_opts, json).wrap_err("Status command failed")?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Some(Commands::Check { json }) => {
+            let code = handle_check_command(&global_opts, json).wrap_err("Check command failed")?;
+            if code != 0 {
+                std::process::exit(code);
+            }
+        }
+        Some(Commands::Watch {
+            debounce,
+            interval,
+            enforcement,
+        }) => {
+            handle_watch_command(&global_opts, debounce, interval, (&enforcement).into())
+                .wrap_err("Watch command failed")?;
+        }
+        Some(Commands::Agent { action }) => {
+            handle_agent_command(&global_opts, action).wrap_err("Agent command failed")?;
+        }
+        Some(Commands::Types {
+            query,
+            category,
+            json,
         }) => {
-            handle_set_command(&global_opts, app_name, ext, scheme, r#type)
-                .wrap_err("Set command failed")?;
+            handle_types_command(query, category, json).wrap_err("Types command failed")?;
         }
         Some(Commands::Init { output }) => {
             handle_init_command(&global_opts, output).wrap_err("Init command failed")?;
@@ -58,8 +125,18 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn handle_config_load(opts: &GlobalOptions) -> Result<()> {
-    let config_path = match &opts.config_path {
+/// Exit code used by `infat status` when an entry has drifted
+const EXIT_DRIFT: i32 = 2;
+
+/// Exit code used by `infat status` when an entry could not be checked
+const EXIT_UNKNOWN: i32 = 3;
+
+/// Exit code used by `infat check` when an entry could not be applied
+const EXIT_CHECK_FAILED: i32 = 1;
+
+/// Locate the configuration file from the global options or default locations
+fn find_config_path(opts: &GlobalOptions) -> Result<PathBuf> {
+    match &opts.config_path {
         Some(path) => {
             if !path.exists() {
                 return Err(color_eyre::eyre::eyre!(
@@ -67,15 +144,50 @@ fn handle_config_load(opts: &GlobalOptions) -> Result<()> {
                     path.display().bright_red()
                 ));
             }
-            path.clone()
+            Ok(path.clone())
         }
         None => config::find_config_file()?.ok_or_else(|| {
             color_eyre::eyre::eyre!(
                 "No configuration file found. Use {} or place config at default location",
                 "--config".bright_yellow()
             )
-        })?,
+        }),
+    }
+}
+
+/// Load the configuration if one was given or exists at a default location
+fn optional_config(opts: &GlobalOptions) -> Result<Option<config::Config>> {
+    let config_path = match &opts.config_path {
+        Some(_) => Some(find_config_path(opts)?),
+        None => config::find_config_file()?,
     };
+    config_path.map(|path| load_config(&path)).transpose()
+}
+
+/// The configuration lookups follow, for its `[settings]` search paths and
+/// `[declare]` extension overrides, or an empty one if there is none or it
+/// can't be loaded
+fn lookup_config(opts: &GlobalOptions) -> config::Config {
+    match optional_config(opts) {
+        Ok(config) => config.unwrap_or_default(),
+        Err(e) => {
+            warn!("Ignoring configuration: {:#}", e);
+            config::Config::default()
+        }
+    }
+}
+
+fn load_config(config_path: &Path) -> Result<config::Config> {
+    config::Config::from_file(config_path).wrap_err_with(|| {
+        format!(
+            "Failed to load configuration from {}",
+            config_path.display().bright_red()
+        )
+    })
+}
+
+fn handle_config_load(opts: &GlobalOptions, enforcement: Enforcement) -> Result<()> {
+    let config_path = find_config_path(opts)?;
 
     if !opts.quiet {
         println!(
@@ -84,12 +196,7 @@ fn handle_config_load(opts: &GlobalOptions) -> Result<()> {
         );
     }
 
-    let config = config::Config::from_file(&config_path).wrap_err_with(|| {
-        format!(
-            "Failed to load configuration from {}",
-            config_path.display().bright_red()
-        )
-    })?;
+    let config = load_config(&config_path)?;
 
     if config.is_empty() {
         return Err(color_eyre::eyre::eyre!(
@@ -100,16 +207,22 @@ fn handle_config_load(opts: &GlobalOptions) -> Result<()> {
     let summary = config.summary();
     if !opts.quiet {
         println!(
-            "{RSCHART_BAR} Found {} associations: {} extensions, {} schemes, {} types",
+            "{RSCHART_BAR} Found {} associations: {} extensions, {} schemes, {} types, {} MIME types, {} file patterns",
             summary.total().to_string().bright_green(),
             summary.extensions_count,
             summary.schemes_count,
-            summary.types_count
+            summary.types_count,
+            summary.mime_count,
+            summary.files_count
         );
     }
 
+    if !opts.quiet {
+        print_expansions(&config::expansions(&config.entries()));
+    }
+
     // Apply configuration
-    config::apply_config(&config, opts.robust)
+    config::apply_config(&config, opts.robust, enforcement)
         .wrap_err("Failed to apply configuration settings")?;
 
     if !opts.quiet {
@@ -123,16 +236,20 @@ fn handle_config_load(opts: &GlobalOptions) -> Result<()> {
 }
 
 fn handle_info_command(
+    opts: &GlobalOptions,
     app: Option<String>,
     ext: Option<String>,
     scheme: Option<String>,
     r#type: Option<String>,
+    mime: Option<String>,
+    view: AppView,
 ) -> Result<()> {
     let provided_count = [
         app.is_some(),
         ext.is_some(),
         scheme.is_some(),
         r#type.is_some(),
+        mime.is_some(),
     ]
     .iter()
     .filter(|&&x| x)
@@ -141,37 +258,81 @@ fn handle_info_command(
     // Some basic validation that clap can't provide
     if provided_count == 0 {
         return Err(color_eyre::eyre::eyre!(
-            "Must provide one of: {}, {}, {}, or {}",
+            "Must provide one of: {}, {}, {}, {}, or {}",
             "--app".bright_yellow(),
             "--ext".bright_yellow(),
             "--scheme".bright_yellow(),
-            "--type".bright_yellow()
+            "--type".bright_yellow(),
+            "--mime".bright_yellow()
         ));
     }
 
     if provided_count > 1 {
         return Err(color_eyre::eyre::eyre!(
-            "Only one of {}, {}, {}, or {} may be provided",
+            "Only one of {}, {}, {}, {}, or {} may be provided",
             "--app".bright_yellow(),
             "--ext".bright_yellow(),
             "--scheme".bright_yellow(),
-            "--type".bright_yellow()
+            "--type".bright_yellow(),
+            "--mime".bright_yellow()
         ));
     }
 
+    let config = lookup_config(opts);
+    let settings = &config.settings;
+
     if let Some(app_name) = app {
         info!("Getting info for application: {}", app_name);
 
-        let app_info = app::get_app_info(&app_name)
+        let app_info = app::get_app_info(&app_name, settings)
             .wrap_err_with(|| format!("Failed to get info for app: {app_name}"))?;
 
         // Display application information
         println!("{}", "Application Information".bright_blue().bold());
         println!("  Name: {}", app_info.name.bright_cyan());
+        if let Some(lang) = &view.lang {
+            match localization::for_language(&app_info.localizations, lang) {
+                Some((folder, names)) => println!(
+                    "  Name ({folder}): {}",
+                    names.preferred().unwrap_or_default().bright_cyan()
+                ),
+                None if app_info.localizations.is_empty() => {
+                    println!("  Name ({lang}): {}", "no localized names".dimmed())
+                }
+                None => {
+                    let available: Vec<&str> =
+                        app_info.localizations.keys().map(String::as_str).collect();
+                    println!(
+                        "  Name ({lang}): {}",
+                        format!("not localized, available: {}", available.join(", ")).dimmed()
+                    );
+                }
+            }
+        }
         println!("  Bundle ID: {}", app_info.bundle_id.bright_green());
         println!("  Version: {}", app_info.version);
         println!("  Path: {}", app_info.path.display().dimmed());
 
+        if app_info.copies.len() > 1 {
+            println!("\n{}", "Installed Copies:".bright_blue().bold());
+            for copy in &app_info.copies {
+                let version = copy.version.as_deref().unwrap_or("unknown version");
+                let selected = match app_info.preference {
+                    Some(preference) if copy.path == app_info.path => {
+                        format!(" (selected: {preference})")
+                            .bright_green()
+                            .to_string()
+                    }
+                    _ => String::new(),
+                };
+                println!(
+                    "  • {} {}{selected}",
+                    version.bright_yellow(),
+                    copy.path.display().dimmed()
+                );
+            }
+        }
+
         // Declared means just those it claims to support
 
         // Display declared URL schemes
@@ -182,35 +343,96 @@ fn handle_info_command(
             }
         }
 
-        // Display declared file types
+        // Display declared file types, strongest claim first
         if !app_info.declared_types.is_empty() {
             println!("\n{}", "Declared File Types:".bright_blue().bold());
-            for declared_type in &app_info.declared_types {
-                println!("  • {}", declared_type.name.bright_cyan());
+            for (rank, declared_types) in app_info.declared_types_by_rank() {
+                let heading = format!("{rank} ({})", declared_types.len());
+                let heading = match rank {
+                    HandlerRank::Owner => heading.bright_green().bold().to_string(),
+                    HandlerRank::Default => heading.bright_cyan().bold().to_string(),
+                    HandlerRank::Alternate => heading.yellow().bold().to_string(),
+                    HandlerRank::None => heading.dimmed().to_string(),
+                };
+                println!("  {heading}");
 
-                if !declared_type.utis.is_empty() {
-                    println!("    UTIs: {}", declared_type.utis.join(", ").dimmed());
-                }
+                for declared_type in declared_types {
+                    let role = declared_type
+                        .role
+                        .map(|role| format!(" [{role}]").bright_magenta().to_string())
+                        .unwrap_or_default();
+                    let implied = if declared_type.rank.is_none() {
+                        " (rank not declared)".dimmed().to_string()
+                    } else {
+                        String::new()
+                    };
+                    println!(
+                        "    • {}{}{}",
+                        declared_type.name.bright_cyan(),
+                        role,
+                        implied
+                    );
 
-                if !declared_type.extensions.is_empty() {
-                    let exts: Vec<String> = declared_type
-                        .extensions
-                        .iter()
-                        .map(|ext| format!(".{ext}"))
-                        .collect();
-                    println!("    Extensions: {}", exts.join(", ").bright_green());
-                }
+                    if !declared_type.utis.is_empty() {
+                        println!("      UTIs: {}", declared_type.utis.join(", ").dimmed());
+                    }
+
+                    if !declared_type.extensions.is_empty() {
+                        let exts: Vec<String> = declared_type
+                            .extensions
+                            .iter()
+                            .map(|ext| format!(".{ext}"))
+                            .collect();
+                        println!("      Extensions: {}", exts.join(", ").bright_green());
+                    }
 
-                if let Some(desc) = &declared_type.description {
-                    println!("    Description: {}", desc.italic());
+                    if let Some(desc) = &declared_type.description {
+                        println!("      Description: {}", desc.italic());
+                    }
                 }
                 println!();
             }
         }
+
+        if view.owned {
+            let report = app::get_app_ownership(&app_info, settings)
+                .wrap_err("Failed to look up the app's handlers")?;
+
+            println!("{}", "Currently Default For:".bright_blue().bold());
+            if report.owned.is_empty() {
+                println!("  {}", "nothing".dimmed());
+            }
+            for ownership in &report.owned {
+                let roles: Vec<String> = ownership
+                    .handler
+                    .roles
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect();
+                let note = match &ownership.coverage {
+                    Some(Coverage::Declared) => String::new(),
+                    Some(Coverage::Ancestor(ancestor)) => {
+                        format!(" (declared as {ancestor})").dimmed().to_string()
+                    }
+                    None => " (not declared)".yellow().to_string(),
+                };
+                println!(
+                    "  • {} [{}]{note}",
+                    ownership.handler.claim.to_string().bright_cyan(),
+                    roles.join(", ")
+                );
+            }
+
+            if !report.declared_unowned.is_empty() {
+                println!("\n{}", "Declared But Not Default For:".bright_blue().bold());
+                for claim in &report.declared_unowned {
+                    println!("  • {}", claim.to_string().dimmed());
+                }
+            }
+        }
     } else if let Some(extension) = ext {
         info!("Getting info for extension: .{}", extension);
-
-        let info = association::get_info_for_extension(&extension)
+        let info = association::get_info_for_extension(&extension, &config)
             .wrap_err_with(|| format!("Failed to get info for extension: .{extension}"))?;
 
         println!(
@@ -219,10 +441,35 @@ fn handle_info_command(
         );
 
         if let Some(uti) = &info.uti {
-            println!("   UTI: {}", uti.bright_cyan());
+            if info.declared_in_config {
+                println!(
+                    "   UTI: {} {}",
+                    uti.bright_cyan(),
+                    "(declared in config)".bright_yellow()
+                );
+                let system_uti = info.system_uti.as_deref().unwrap_or("None");
+                println!("   System UTI: {} {}", system_uti, "(overridden)".dimmed());
+            } else {
+                println!("   UTI: {}", uti.bright_cyan());
+            }
+            if let Some(dynamic) = DynamicUti::decode(uti) {
+                println!("   Dynamic type for: {}", dynamic.to_string().dimmed());
+            }
+        }
+
+        if let Some(mime_type) = &info.mime_type {
+            println!("   MIME type: {}", mime_type.bright_cyan());
+        }
+
+        if let Some(declaration) = info
+            .declaration
+            .as_ref()
+            .filter(|d| d.declared_by.is_some())
+        {
+            println!("   Declared by: {}", declared_by(declaration));
         }
 
-        match info.default_app_name()? {
+        match info.default_app_name(settings)? {
             Some(app_name) => {
                 println!("   Default app: {}", app_name.bright_yellow());
             }
@@ -231,7 +478,7 @@ fn handle_info_command(
             }
         }
 
-        let all_app_names = info.all_app_names();
+        let all_app_names = info.all_app_names(settings);
         if !all_app_names.is_empty() {
             println!("\n{}", "All registered apps:".bright_blue().bold());
             for app_name in all_app_names {
@@ -251,7 +498,7 @@ fn handle_info_command(
 
         println!("{RSLINK} URL Scheme: {}", url_scheme.bright_green());
 
-        match info.default_app_name()? {
+        match info.default_app_name(settings)? {
             Some(app_name) => {
                 println!("   Default app: {}", app_name.bright_yellow());
             }
@@ -260,7 +507,7 @@ fn handle_info_command(
             }
         }
 
-        let all_app_names = info.all_app_names();
+        let all_app_names = info.all_app_names(settings);
         if !all_app_names.is_empty() {
             println!("\n{}", "All registered apps:".bright_blue().bold());
             for app_name in all_app_names {
@@ -275,16 +522,42 @@ fn handle_info_command(
     } else if let Some(type_name) = r#type {
         info!("Getting info for type: {}", type_name);
 
-        let info = association::get_info_for_type(&type_name)
+        let info = association::get_info_for_type(&type_name, &config)
             .wrap_err_with(|| format!("Failed to get info for type: {type_name}"))?;
 
         println!("{RSTAG}  File Type: {}", type_name.bright_green());
 
         if let Some(uti) = &info.uti {
             println!("    UTI: {}", uti.bright_cyan());
+            if let Some(dynamic) = DynamicUti::decode(uti) {
+                println!("    Dynamic type for: {}", dynamic.to_string().dimmed());
+            }
         }
 
-        match info.default_app_name()? {
+        if let Some(mime_type) = &info.mime_type {
+            println!("    MIME type: {}", mime_type.bright_cyan());
+        }
+
+        if let Some(declaration) = &info.declaration {
+            println!("    Declared by: {}", declared_by(declaration));
+        }
+
+        if !info.tags.is_empty() {
+            println!("\n{}", "Tags:".bright_blue().bold());
+            for (class, tags) in info.tags.groups() {
+                let tags: Vec<String> = tags
+                    .iter()
+                    .map(|tag| match class {
+                        TagClass::Extension => format!(".{tag}"),
+                        TagClass::MimeType => tag.clone(),
+                        TagClass::OsType => format!("'{tag}'"),
+                    })
+                    .collect();
+                println!("  {class}: {}", tags.join(", ").bright_green());
+            }
+        }
+
+        match info.default_app_name(settings)? {
             Some(app_name) => {
                 println!("    Default app: {}", app_name.bright_yellow());
             }
@@ -293,7 +566,7 @@ fn handle_info_command(
             }
         }
 
-        let all_app_names = info.all_app_names();
+        let all_app_names = info.all_app_names(settings);
         if !all_app_names.is_empty() {
             println!("\n{}", "All registered apps:".bright_blue().bold());
             for app_name in all_app_names {
@@ -302,17 +575,72 @@ fn handle_info_command(
         } else {
             println!("\n{}", "No applications registered for this type".yellow());
         }
+    } else if let Some(mime_type) = mime {
+        info!("Getting info for MIME type: {}", mime_type);
+
+        let info = association::get_info_for_mime_type(&mime_type, &config)
+            .wrap_err_with(|| format!("Failed to get info for MIME type: {mime_type}"))?;
+
+        println!("{RSTAG}  MIME Type: {}", info.identifier.bright_green());
+
+        if let Some(uti) = &info.uti {
+            println!("    UTI: {}", uti.bright_cyan());
+            if let Some(dynamic) = DynamicUti::decode(uti) {
+                println!("    Dynamic type for: {}", dynamic.to_string().dimmed());
+            }
+        }
+
+        if let Some(declaration) = &info.declaration {
+            println!("    Declared by: {}", declared_by(declaration));
+        }
+
+        match info.default_app_name(settings)? {
+            Some(app_name) => {
+                println!("    Default app: {}", app_name.bright_yellow());
+            }
+            None => {
+                println!("    Default app: {}", "None".bright_red());
+            }
+        }
+
+        let all_app_names = info.all_app_names(settings);
+        if !all_app_names.is_empty() {
+            println!("\n{}", "All registered apps:".bright_blue().bold());
+            for app_name in all_app_names {
+                println!("  • {app_name}");
+            }
+        } else {
+            println!(
+                "\n{}",
+                "No applications registered for this MIME type".yellow()
+            );
+        }
     }
 
     Ok(())
 }
 
+/// Describe who declared a type, for `info`
+fn declared_by(declaration: &TypeDeclaration) -> String {
+    match &declaration.declared_by {
+        Some(app) if app.exported => app.to_string().bright_yellow().to_string(),
+        Some(app) => format!(
+            "{} {}",
+            app.to_string().bright_yellow(),
+            "(imported)".dimmed()
+        ),
+        None => "macOS".dimmed().to_string(),
+    }
+}
+
 fn handle_set_command(
     opts: &GlobalOptions,
     app_name: String,
     ext: Option<String>,
     scheme: Option<String>,
     r#type: Option<String>,
+    recursive: bool,
+    enforcement: Enforcement,
 ) -> Result<()> {
     let provided_count = [ext.is_some(), scheme.is_some(), r#type.is_some()]
         .iter()
@@ -339,8 +667,9 @@ fn handle_set_command(
 
     if let Some(extension) = ext {
         info!("Setting {} as default for .{}", app_name, extension);
+        let config = lookup_config(opts);
 
-        association::set_default_app_for_extension(&extension, &app_name)
+        association::set_default_app_for_extension(&extension, &app_name, &config, enforcement)
             .wrap_err_with(|| format!("Failed to set default app for .{extension}"))?;
 
         if !opts.quiet {
@@ -354,7 +683,9 @@ fn handle_set_command(
     } else if let Some(url_scheme) = scheme {
         info!("Setting {} as default for {} scheme", app_name, url_scheme);
 
-        association::set_default_app_for_url_scheme(&url_scheme, &app_name)
+        let config = lookup_config(opts);
+
+        association::set_default_app_for_url_scheme(&url_scheme, &app_name, &config, enforcement)
             .wrap_err_with(|| format!("Failed to set default app for {url_scheme} scheme"))?;
 
         if !opts.quiet {
@@ -368,16 +699,440 @@ fn handle_set_command(
     } else if let Some(type_name) = r#type {
         info!("Setting {} as default for type {}", app_name, type_name);
 
-        association::set_default_app_for_type(&type_name, &app_name)
-            .wrap_err_with(|| format!("Failed to set default app for type {type_name}"))?;
+        // Names from the config's [supertypes] table work like built-in ones
+        let config = match optional_config(opts)? {
+            Some(config) if !config.is_empty() => {
+                config.validate().wrap_err("Invalid configuration")?;
+                config
+            }
+            config => config.unwrap_or_default(),
+        };
+        let binding = config::Binding {
+            recursive,
+            ..config::Binding::from(app_name.as_str())
+        };
 
+        let entries = config.type_entries(&type_name, &binding);
         if !opts.quiet {
+            print_expansions(&config::expansions(&entries));
+        }
+
+        // Like applying a configuration, one failed type doesn't stop the rest
+        let mut failed = 0;
+        for entry in &entries {
+            match config::apply_entry(entry, &config, enforcement) {
+                Ok(()) => {
+                    if !opts.quiet {
+                        println!(
+                            "{} Set {} → {}",
+                            "✓".bright_green(),
+                            entry,
+                            app_name.bright_cyan()
+                        );
+                    }
+                }
+                Err(e) => {
+                    failed += 1;
+                    warn!("Failed to set {entry} → {}: {e}", entry.binding);
+                }
+            }
+        }
+
+        if failed > 0 && !opts.robust {
+            return Err(color_eyre::eyre::eyre!(
+                "Failed to set {} of {} associations",
+                failed,
+                entries.len()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// List the types each `recursive` entry expands to
+fn print_expansions(expanded: &BTreeMap<String, Vec<String>>) {
+    for (name, utis) in expanded {
+        println!(
+            "  {} {} expands to {} types: {}",
+            "↳".bright_blue(),
+            name.bright_yellow(),
+            utis.len(),
+            utis.join(", ")
+        );
+    }
+}
+
+/// How an entry is named in `status` and `check` output
+fn entry_label(kind: EntryKind, key: &str, supertype: Option<&str>) -> String {
+    let label = match kind {
+        EntryKind::Extension => format!(".{key}"),
+        EntryKind::Scheme => format!("{key}://"),
+        EntryKind::Type | EntryKind::Mime | EntryKind::File => key.to_string(),
+    };
+    match supertype {
+        Some(supertype) => format!("{label} (via {supertype})"),
+        None => label,
+    }
+}
+
+fn handle_check_command(opts: &GlobalOptions, json: bool) -> Result<i32> {
+    let config_path = find_config_path(opts)?;
+    let config = load_config(&config_path)?;
+    config.validate().wrap_err("Invalid configuration")?;
+
+    let roots = config.settings.search_paths();
+    let index = app_index::shared(&roots).wrap_err("Failed to load the application index")?;
+    let graph = UtiGraph::installed(&roots).wrap_err("Failed to load declared types")?;
+    let report = check::check_config(&config, &index, &graph);
+    let code = if report.has_errors() {
+        EXIT_CHECK_FAILED
+    } else {
+        0
+    };
+
+    if json {
+        let output = serde_json::json!({
+            "config": config_path,
+            "checked": report.checked,
+            "errors": report.errors().count(),
+            "warnings": report.warnings().count(),
+            "issues": report.issues,
+            "expanded": report.expanded,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(code);
+    }
+
+    if !opts.quiet {
+        println!(
+            "{RSFILE_DOCUMENT} Checking configuration from: {}",
+            config_path.display().bright_cyan()
+        );
+    }
+
+    for issue in &report.issues {
+        let label = entry_label(issue.kind, &issue.key, issue.supertype.as_deref());
+        let marker = match issue.severity {
+            Severity::Error => "✗".bright_red().to_string(),
+            Severity::Warning => "!".yellow().to_string(),
+        };
+        println!(
+            "  {} {}: {}{}",
+            marker,
+            label.bright_yellow(),
+            issue.message,
+            issue.suggestions.bright_cyan()
+        );
+    }
+
+    if !opts.quiet {
+        print_expansions(&report.expanded);
+
+        if report.issues.is_empty() {
             println!(
-                "{} Set type {} → {}",
-                "✓".bright_green(),
-                type_name,
-                app_name.bright_cyan()
+                "{RSCHECK} {}",
+                format!("All {} entries look good", report.checked).bright_green()
+            );
+        } else {
+            println!(
+                "{RSCHART_BAR} {} errors and {} warnings in {} entries",
+                report.errors().count().to_string().bright_red(),
+                report.warnings().count().to_string().yellow(),
+                report.checked
+            );
+        }
+    }
+
+    Ok(code)
+}
+
+fn handle_status_command(opts: &GlobalOptions, json: bool) -> Result<i32> {
+    let config_path = find_config_path(opts)?;
+    let config = load_config(&config_path)?;
+
+    let report = status::check_config(&config).wrap_err("Failed to check configuration")?;
+    let code = if report.has_drift() {
+        EXIT_DRIFT
+    } else if report.has_unknown() {
+        EXIT_UNKNOWN
+    } else {
+        0
+    };
+
+    if json {
+        let output = serde_json::json!({
+            "config": config_path,
+            "drifted": report.drifted().count(),
+            "unknown": report.unknown().count(),
+            "entries": report.entries,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(code);
+    }
+
+    if !opts.quiet {
+        println!(
+            "{RSFILE_DOCUMENT} Checking configuration from: {}",
+            config_path.display().bright_cyan()
+        );
+    }
+
+    for entry in &report.entries {
+        let label = entry_label(entry.kind, &entry.key, entry.supertype.as_deref());
+
+        match &entry.state {
+            EntryState::Matches => {
+                if !opts.quiet {
+                    println!(
+                        "  {} {} → {}",
+                        "✓".bright_green(),
+                        label,
+                        entry.expected_app.bright_cyan()
+                    );
+                }
+            }
+            EntryState::Drifted => {
+                let actual = entry.actual_app.as_deref().unwrap_or("None");
+                println!(
+                    "  {} {} expected {} ({}), found {} ({})",
+                    "✗".bright_red(),
+                    label.bright_yellow(),
+                    entry.expected_app.bright_cyan(),
+                    entry.expected_bundle_id.as_deref().unwrap_or("?").dimmed(),
+                    actual.bright_red(),
+                    entry.actual_bundle_id.as_deref().unwrap_or("-").dimmed()
+                );
+            }
+            EntryState::Unknown { reason } => {
+                println!(
+                    "  {} {} could not be checked: {}",
+                    "?".yellow(),
+                    label.bright_yellow(),
+                    reason.dimmed()
+                );
+            }
+        }
+    }
+
+    if !opts.quiet {
+        if report.is_clean() {
+            println!(
+                "{RSCHECK} {}",
+                "All associations match the configuration".bright_green()
+            );
+        } else {
+            let drifted = report.drifted().count();
+            let unknown = report.unknown().count();
+            let mut summary = format!(
+                "{} of {} associations have drifted",
+                drifted.to_string().bright_red(),
+                report.entries.len()
+            );
+            if unknown > 0 {
+                summary.push_str(&format!(
+                    ", {} could not be checked",
+                    unknown.to_string().yellow()
+                ));
+            }
+            if drifted > 0 {
+                summary.push_str(&format!(", run {} to re-apply", "infat".bright_yellow()));
+            }
+            println!("{RSCHART_BAR} {summary}");
+        }
+    }
+
+    Ok(code)
+}
+
+fn handle_watch_command(
+    opts: &GlobalOptions,
+    debounce: u64,
+    interval: u64,
+    enforcement: Enforcement,
+) -> Result<()> {
+    let config_path = find_config_path(opts)?;
+
+    let options = watch::WatchOptions {
+        debounce: Duration::from_millis(debounce),
+    };
+    let mut watcher = watch::watch_config(
+        &config_path,
+        Duration::from_millis(interval),
+        options,
+        enforcement,
+    )
+    .wrap_err_with(|| {
+        format!(
+            "Failed to watch configuration {}",
+            config_path.display().bright_red()
+        )
+    })?;
+
+    if !opts.quiet {
+        println!(
+            "{RSFILE_SEARCH} Watching {} and the Launch Services database (Ctrl-C to stop)",
+            config_path.display().bright_cyan()
+        );
+    }
+
+    watcher.run(|reversion| {
+        if opts.quiet {
+            return;
+        }
+
+        println!(
+            "{} Reverted {}: {} → {}",
+            "↺".bright_yellow(),
+            entry_label(
+                reversion.kind,
+                &reversion.key,
+                reversion.supertype.as_deref()
+            ),
+            reversion.previous.as_deref().unwrap_or("None").bright_red(),
+            reversion.app.bright_cyan()
+        );
+    })?;
+
+    Ok(())
+}
+
+fn agent_options(opts: &GlobalOptions, args: AgentArgs) -> Result<agent::AgentOptions> {
+    let program = match args.program {
+        Some(program) => program,
+        None => std::env::current_exe().wrap_err("Could not locate the infat executable")?,
+    };
+
+    // launchd doesn't know about our default locations, so always pin the config path
+    let config_path = match &opts.config_path {
+        Some(path) => Some(std::path::absolute(path)?),
+        None => config::find_config_file()?,
+    };
+
+    let mut options = agent::AgentOptions::new(program);
+    options.label = args.label;
+    options.config_path = config_path;
+    options.interval = args.interval;
+    options.watch_config = args.watch_config;
+    options.stdout_path = args.stdout;
+    options.stderr_path = args.stderr;
+    options.robust = opts.robust;
+
+    Ok(options)
+}
+
+fn handle_agent_command(opts: &GlobalOptions, action: AgentAction) -> Result<()> {
+    match action {
+        AgentAction::Print(args) => {
+            let options = agent_options(opts, args)?;
+            print!("{}", agent::render(&options)?);
+        }
+        AgentAction::Install(args) => {
+            let options = agent_options(opts, args)?;
+            let path = agent::install(&options).wrap_err("Failed to install LaunchAgent")?;
+
+            if !opts.quiet {
+                println!(
+                    "{RSCHECK} Installed LaunchAgent {} at {}",
+                    options.label.bright_green(),
+                    path.display().bright_cyan()
+                );
+            }
+        }
+        AgentAction::Uninstall { label } => {
+            let removed = agent::uninstall(&label).wrap_err("Failed to uninstall LaunchAgent")?;
+
+            if !opts.quiet {
+                if removed {
+                    println!("{RSCHECK} Removed LaunchAgent {}", label.bright_green());
+                } else {
+                    println!(
+                        "No LaunchAgent named {} is installed",
+                        label.bright_yellow()
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extensions shown for each supertype in `infat types`
+const EXAMPLE_EXTENSIONS: usize = 5;
+
+fn handle_types_command(query: Option<String>, category: Option<String>, json: bool) -> Result<()> {
+    let category = category.map(|name| name.parse::<Category>()).transpose()?;
+
+    let matches: Vec<_> = SuperType::catalogue()
+        .iter()
+        .filter(|info| category.is_none_or(|category| info.category == category))
+        .filter(|info| query.as_deref().is_none_or(|query| info.matches(query)))
+        .collect();
+
+    if json {
+        let output: Vec<_> = matches
+            .iter()
+            .map(|info| {
+                serde_json::json!({
+                    "name": info.name,
+                    "uti": info.uti,
+                    "category": info.category,
+                    "aliases": info.aliases,
+                    "extensions": info.example_extensions(EXAMPLE_EXTENSIONS),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if matches.is_empty() {
+        println!("{}", "No supertypes match".yellow());
+        return Ok(());
+    }
+
+    let width = matches
+        .iter()
+        .map(|info| info.name.len())
+        .max()
+        .unwrap_or(0);
+    let uti_width = matches.iter().map(|info| info.uti.len()).max().unwrap_or(0);
+
+    for (i, category) in Category::ALL.iter().enumerate() {
+        let rows: Vec<_> = matches
+            .iter()
+            .filter(|info| info.category == *category)
+            .collect();
+        if rows.is_empty() {
+            continue;
+        }
+
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "{} {}",
+            category.label().bright_blue().bold(),
+            format!("({category})").dimmed()
+        );
+
+        for info in rows {
+            let extensions: Vec<String> = info
+                .example_extensions(EXAMPLE_EXTENSIONS)
+                .iter()
+                .map(|ext| format!(".{ext}"))
+                .collect();
+            print!(
+                "  {:width$}  {:uti_width$}  {}",
+                info.name.bright_cyan(),
+                info.uti.dimmed(),
+                extensions.join(" ").bright_green()
             );
+            if !info.aliases.is_empty() {
+                print!("  {}", format!("aka {}", info.aliases.join(", ")).dimmed());
+            }
+            println!();
         }
     }
 
@@ -391,18 +1146,20 @@ fn handle_init_command(opts: &GlobalOptions, output: Option<PathBuf>) -> Result<
         println!("{RSFILE_SEARCH} Reading Launch Services database...");
     }
 
-    let config = launch_services_db::generate_config_from_launch_services(opts.robust)
+    let settings = lookup_config(opts).settings;
+    let config = launch_services_db::generate_config_from_launch_services(opts.robust, &settings)
         .wrap_err("Failed to generate configuration from Launch Services database")?;
 
     let summary = config.summary();
 
     if !opts.quiet {
         println!(
-            "{RSCHART_BAR} Generated {} associations: {} extensions, {} schemes, {} types",
+            "{RSCHART_BAR} Generated {} associations: {} extensions, {} schemes, {} types, {} MIME types",
             summary.total().to_string().bright_green(),
             summary.extensions_count,
             summary.schemes_count,
-            summary.types_count
+            summary.types_count,
+            summary.mime_count
         );
     }
 
@@ -0,0 +1,104 @@
+//! Compares single-threaded and parallel application discovery on a synthetic
+//! tree of fake `.app` bundles laid out like a well-stocked `/Applications`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use infat_lib::app_index::AppIndex;
+use infat_lib::discovery::{self, SearchPath, WalkOptions};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Vendor folders (think `Adobe`, `Setapp`, `Utilities`) under the root
+const VENDORS: usize = 40;
+/// Nested folders inside each vendor folder
+const SUITES: usize = 5;
+/// Bundles inside each suite folder
+const APPS_PER_SUITE: usize = 20;
+
+fn info_plist(bundle_id: &str, name: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>CFBundleIdentifier</key>
+    <string>{bundle_id}</string>
+    <key>CFBundleName</key>
+    <string>{name}</string>
+    <key>CFBundleShortVersionString</key>
+    <string>1.0</string>
+</dict>
+</plist>
+"#
+    )
+}
+
+fn make_app(dir: &Path, name: &str, bundle_id: &str) {
+    let contents = dir.join(format!("{name}.app")).join("Contents");
+    fs::create_dir_all(contents.join("MacOS")).unwrap();
+    fs::create_dir_all(contents.join("Resources")).unwrap();
+    fs::write(contents.join("Info.plist"), info_plist(bundle_id, name)).unwrap();
+}
+
+/// Build the synthetic tree, returning the temp dir (which must outlive the bench)
+fn synthetic_tree() -> (TempDir, Vec<SearchPath>) {
+    let tmp = TempDir::new().unwrap();
+    let root = tmp.path().join("Applications");
+
+    for vendor in 0..VENDORS {
+        let vendor_dir = root.join(format!("Vendor {vendor}"));
+        make_app(
+            &vendor_dir,
+            &format!("Launcher {vendor}"),
+            &format!("com.vendor{vendor}.launcher"),
+        );
+
+        for suite in 0..SUITES {
+            let suite_dir = vendor_dir.join(format!("Suite {suite}"));
+            for app in 0..APPS_PER_SUITE {
+                make_app(
+                    &suite_dir,
+                    &format!("App {vendor}-{suite}-{app}"),
+                    &format!("com.vendor{vendor}.suite{suite}.app{app}"),
+                );
+            }
+        }
+    }
+
+    (tmp, vec![SearchPath::new(root)])
+}
+
+fn bench_discovery(c: &mut Criterion) {
+    let (_tmp, roots) = synthetic_tree();
+    let expected = VENDORS * (SUITES * APPS_PER_SUITE + 1);
+    assert_eq!(
+        discovery::walk(&roots, &WalkOptions::default()).apps.len(),
+        expected
+    );
+
+    let serial = WalkOptions {
+        threads: Some(1),
+        ..WalkOptions::default()
+    };
+    let parallel = WalkOptions::default();
+
+    let mut group = c.benchmark_group(format!("discovery/{expected}-apps"));
+    group.sample_size(20);
+
+    for (label, options) in [("serial", &serial), ("parallel", &parallel)] {
+        group.bench_with_input(BenchmarkId::new("walk", label), options, |b, options| {
+            b.iter(|| discovery::walk(&roots, options))
+        });
+    }
+
+    for (label, options) in [("serial", &serial), ("parallel", &parallel)] {
+        group.bench_with_input(BenchmarkId::new("index", label), options, |b, options| {
+            b.iter(|| AppIndex::build_with(&roots, options))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_discovery);
+criterion_main!(benches);
@@ -0,0 +1,269 @@
+//! LaunchAgent generation for applying the configuration at login
+
+use crate::error::{InfatError, Result};
+use plist::{Dictionary, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Label used for the agent unless another is given
+pub const DEFAULT_LABEL: &str = "com.philocalyst.infat";
+
+/// What the generated LaunchAgent should run and when
+#[derive(Debug, Clone)]
+pub struct AgentOptions {
+    /// launchd label, also used as the plist file name
+    pub label: String,
+
+    /// The `infat` executable to run
+    pub program: PathBuf,
+
+    /// Configuration file passed with `--config`
+    pub config_path: Option<PathBuf>,
+
+    /// Also run every this many seconds
+    pub interval: Option<u64>,
+
+    /// Also run whenever the configuration file changes
+    pub watch_config: bool,
+
+    /// Where launchd should write standard output
+    pub stdout_path: Option<PathBuf>,
+
+    /// Where launchd should write standard error
+    pub stderr_path: Option<PathBuf>,
+
+    /// Pass `--robust` so one missing app doesn't stop the rest
+    pub robust: bool,
+}
+
+impl AgentOptions {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            label: DEFAULT_LABEL.to_string(),
+            program: program.into(),
+            config_path: None,
+            interval: None,
+            watch_config: false,
+            stdout_path: None,
+            stderr_path: None,
+            robust: false,
+        }
+    }
+
+    fn program_arguments(&self) -> Vec<Value> {
+        let mut args = vec![self.program.display().to_string()];
+
+        if let Some(config_path) = &self.config_path {
+            args.push("--config".to_string());
+            args.push(config_path.display().to_string());
+        }
+
+        if self.robust {
+            args.push("--robust".to_string());
+        }
+
+        args.into_iter().map(Value::String).collect()
+    }
+}
+
+/// Build the LaunchAgent property list
+pub fn agent_plist(options: &AgentOptions) -> Result<Value> {
+    let mut dict = Dictionary::new();
+
+    dict.insert("Label".into(), options.label.clone().into());
+    dict.insert(
+        "ProgramArguments".into(),
+        Value::Array(options.program_arguments()),
+    );
+    dict.insert("RunAtLoad".into(), true.into());
+
+    if let Some(interval) = options.interval {
+        dict.insert("StartInterval".into(), Value::Integer(interval.into()));
+    }
+
+    if options.watch_config {
+        let config_path =
+            options
+                .config_path
+                .as_ref()
+                .ok_or_else(|| InfatError::ConflictingOptions {
+                    message: "watching the config requires a config path".to_string(),
+                })?;
+
+        dict.insert(
+            "WatchPaths".into(),
+            Value::Array(vec![config_path.display().to_string().into()]),
+        );
+    }
+
+    if let Some(path) = &options.stdout_path {
+        dict.insert("StandardOutPath".into(), path.display().to_string().into());
+    }
+
+    if let Some(path) = &options.stderr_path {
+        dict.insert(
+            "StandardErrorPath".into(),
+            path.display().to_string().into(),
+        );
+    }
+
+    Ok(Value::Dictionary(dict))
+}
+
+/// Render the LaunchAgent as an XML property list
+pub fn render(options: &AgentOptions) -> Result<String> {
+    let mut buffer = Vec::new();
+    agent_plist(options)?
+        .to_writer_xml(&mut buffer)
+        .map_err(|e| InfatError::Generic {
+            message: format!("Failed to render LaunchAgent: {e}"),
+        })?;
+    buffer.push(b'\n');
+
+    String::from_utf8(buffer).map_err(|e| InfatError::Generic {
+        message: format!("LaunchAgent is not valid UTF-8: {e}"),
+    })
+}
+
+/// Where the LaunchAgent with the given label is installed
+pub fn agent_path(label: &str) -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| InfatError::Generic {
+        message: "Could not determine home directory".to_string(),
+    })?;
+
+    Ok(home
+        .join("Library")
+        .join("LaunchAgents")
+        .join(format!("{label}.plist")))
+}
+
+/// Write the LaunchAgent and load it into the current login session
+pub fn install(options: &AgentOptions) -> Result<PathBuf> {
+    let path = agent_path(&options.label)?;
+    let contents = render(options)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // launchd keeps the old definition until the agent is unloaded
+    if path.exists() {
+        unload(&options.label);
+    }
+
+    fs::write(&path, contents)?;
+    info!("Wrote LaunchAgent to {}", path.display());
+
+    load(&path);
+    Ok(path)
+}
+
+/// Unload and remove the LaunchAgent, returning whether one was installed
+pub fn uninstall(label: &str) -> Result<bool> {
+    let path = agent_path(label)?;
+
+    if !path.exists() {
+        debug!("No LaunchAgent installed at {}", path.display());
+        return Ok(false);
+    }
+
+    unload(label);
+    fs::remove_file(&path)?;
+    info!("Removed LaunchAgent {}", path.display());
+
+    Ok(true)
+}
+
+/// The launchd domain for the current user's GUI session
+#[cfg(target_os = "macos")]
+fn gui_domain() -> String {
+    format!("gui/{}", unsafe { libc::getuid() })
+}
+
+#[cfg(target_os = "macos")]
+fn launchctl(args: &[&str]) {
+    match std::process::Command::new("launchctl").args(args).output() {
+        Ok(output) if output.status.success() => debug!("launchctl {:?} succeeded", args),
+        Ok(output) => warn!(
+            "launchctl {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => warn!("Could not run launchctl: {}", e),
+    }
+}
+
+// Failing to (un)load isn't fatal: launchd picks the file up at the next login either way
+#[cfg(target_os = "macos")]
+fn load(path: &Path) {
+    launchctl(&["bootstrap", &gui_domain(), &path.display().to_string()]);
+}
+
+#[cfg(target_os = "macos")]
+fn unload(label: &str) {
+    launchctl(&["bootout", &format!("{}/{label}", gui_domain())]);
+}
+
+#[cfg(not(target_os = "macos"))]
+fn load(path: &Path) {
+    warn!(
+        "launchd is unavailable, {} will not be loaded",
+        path.display()
+    );
+}
+
+#[cfg(not(target_os = "macos"))]
+fn unload(label: &str) {
+    warn!("launchd is unavailable, {} will not be unloaded", label);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_login_agent() {
+        let mut options = AgentOptions::new("/usr/local/bin/infat");
+        options.config_path = Some("/Users/me/.config/infat/config.toml".into());
+        options.interval = Some(3600);
+        options.watch_config = true;
+        options.stdout_path = Some("/tmp/infat.log".into());
+
+        let value = agent_plist(&options).unwrap();
+        let dict = value.as_dictionary().unwrap();
+
+        assert_eq!(dict["Label"].as_string(), Some(DEFAULT_LABEL));
+        assert_eq!(dict["RunAtLoad"].as_boolean(), Some(true));
+        assert_eq!(dict["StartInterval"].as_unsigned_integer(), Some(3600));
+        assert_eq!(
+            dict["ProgramArguments"].as_array().unwrap(),
+            &[
+                Value::from("/usr/local/bin/infat"),
+                Value::from("--config"),
+                Value::from("/Users/me/.config/infat/config.toml"),
+            ]
+        );
+        assert_eq!(
+            dict["WatchPaths"].as_array().unwrap(),
+            &[Value::from("/Users/me/.config/infat/config.toml")]
+        );
+        assert_eq!(dict["StandardOutPath"].as_string(), Some("/tmp/infat.log"));
+        assert!(!dict.contains_key("StandardErrorPath"));
+
+        // The rendered XML parses back to the same agent
+        let rendered = render(&options).unwrap();
+        assert_eq!(Value::from_reader_xml(rendered.as_bytes()).unwrap(), value);
+    }
+
+    #[test]
+    fn watching_requires_a_config_path() {
+        let mut options = AgentOptions::new("/usr/local/bin/infat");
+        options.watch_config = true;
+
+        assert!(matches!(
+            agent_plist(&options),
+            Err(InfatError::ConflictingOptions { .. })
+        ));
+    }
+}
@@ -1,11 +1,18 @@
 //! Application information and management
 
 use crate::{
+    app_index::{self, CopyPreference, IndexedApp},
+    bundle::{BundleInfo, BundleTypeRole, DocumentType, HandlerRank},
+    capability::{Capabilities, OwnershipReport},
+    config::Settings,
     error::{InfatError, Result},
-    macos::workspace,
+    localization::{self, LocalizedNames},
+    macos::{launch_services_db, workspace},
+    suggest::Suggestions,
+    uti::UtiGraph,
 };
-use plist::Value;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 /// Information about an application's declared file types and URL schemes
@@ -17,6 +24,12 @@ pub struct AppInfo {
     pub path: PathBuf,
     pub declared_types: Vec<DeclaredType>,
     pub declared_schemes: Vec<String>,
+    /// Names from `*.lproj/InfoPlist.strings`, keyed by `.lproj` folder name
+    pub localizations: BTreeMap<String, LocalizedNames>,
+    /// Every installed copy of the bundle ID, the one described here first
+    pub copies: Vec<IndexedApp>,
+    /// Why the first copy was chosen, when there are several
+    pub preference: Option<CopyPreference>,
 }
 
 /// A file type or UTI declared by an application
@@ -26,71 +39,97 @@ pub struct DeclaredType {
     pub utis: Vec<String>,
     pub extensions: Vec<String>,
     pub description: Option<String>,
+    /// `LSHandlerRank`, if the app declared one
+    pub rank: Option<HandlerRank>,
+    /// `CFBundleTypeRole`, if the app declared one
+    pub role: Option<BundleTypeRole>,
+}
+
+impl DeclaredType {
+    /// The rank Launch Services uses for the type; `Default` when none is declared
+    pub fn effective_rank(&self) -> HandlerRank {
+        self.rank.unwrap_or(HandlerRank::Default)
+    }
+}
+
+impl AppInfo {
+    /// Declared types grouped by rank, strongest claim first
+    pub fn declared_types_by_rank(&self) -> BTreeMap<HandlerRank, Vec<&DeclaredType>> {
+        let mut by_rank: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for declared_type in &self.declared_types {
+            by_rank
+                .entry(declared_type.effective_rank())
+                .or_default()
+                .push(declared_type);
+        }
+        by_rank
+    }
 }
 
 /// Get detailed information about an application
-pub fn get_app_info(app_name_or_bundle_id: &str) -> Result<AppInfo> {
+pub fn get_app_info(app_name_or_bundle_id: &str, settings: &Settings) -> Result<AppInfo> {
     debug!("Getting app info for: {}", app_name_or_bundle_id);
 
     // Find the application
-    let app_path = workspace::find_application(app_name_or_bundle_id)?.ok_or_else(|| {
-        InfatError::ApplicationNotFound {
-            name: app_name_or_bundle_id.to_string(),
-        }
-    })?;
-
-    // Get bundle ID
-    let bundle_id = workspace::get_bundle_id_from_app_path(&app_path)?;
-
-    // Read Info.plist
-    let info_plist_path = app_path.join("Contents").join("Info.plist");
-    let plist_data = std::fs::read(&info_plist_path)?;
-    let plist: Value = plist::from_bytes(&plist_data).map_err(|e| InfatError::PlistReadError {
-        path: info_plist_path.clone(),
-        source: Box::new(e),
-    })?;
-
-    let dict = plist
-        .as_dictionary()
-        .ok_or_else(|| InfatError::PlistReadError {
-            path: info_plist_path.clone(),
-            source: "Info.plist root is not a dictionary".into(),
+    let app_path =
+        workspace::find_application(app_name_or_bundle_id, settings)?.ok_or_else(|| {
+            InfatError::ApplicationNotFound {
+                name: app_name_or_bundle_id.to_string(),
+                suggestions: Suggestions::installed_apps(
+                    app_name_or_bundle_id,
+                    &settings.search_paths(),
+                ),
+            }
         })?;
 
-    // Get app name
-    let name = dict
-        .get("CFBundleDisplayName")
-        .or_else(|| dict.get("CFBundleName"))
-        .and_then(|val| val.as_string())
-        .unwrap_or("Unknown")
-        .to_string();
-
-    // Get version
-    let version = dict
-        .get("CFBundleShortVersionString")
-        .or_else(|| dict.get("CFBundleVersion"))
-        .and_then(|val| val.as_string())
-        .unwrap_or("Unknown")
-        .to_string();
-
-    // Parse declared document types
-    let declared_types = parse_document_types(dict);
+    let bundle_id = workspace::get_bundle_id_from_app_path(&app_path, settings)?;
+    let info = BundleInfo::load(&app_path)?;
 
-    // Parse declared URL schemes
-    let declared_schemes = parse_url_schemes(dict);
+    // A path picks its copy; a name or bundle ID got the preferred one
+    let explicit = (app_name_or_bundle_id.ends_with(".app")
+        && Path::new(app_name_or_bundle_id).exists())
+    .then_some(app_path.as_path());
+    let copies = workspace::installed_copies(&bundle_id, explicit, settings)?;
+    let preference = app_index::preference_of(
+        &copies.iter().collect::<Vec<_>>(),
+        explicit,
+        settings.prefer_copy,
+    );
 
     Ok(AppInfo {
         bundle_id,
-        name,
-        version,
-        path: app_path,
-        declared_types,
-        declared_schemes,
+        name: info.preferred_name().unwrap_or("Unknown").to_string(),
+        version: info.preferred_version().unwrap_or("Unknown").to_string(),
+        path: app_path.clone(),
+        declared_types: info.document_types.iter().map(DeclaredType::from).collect(),
+        declared_schemes: info.url_schemes().map(str::to_string).collect(),
+        localizations: localization::load(&app_path),
+        copies,
+        preference,
     })
 }
 
+/// Compare what an application is the default for in the Launch Services
+/// database with what its `Info.plist` declares
+pub fn get_app_ownership(app_info: &AppInfo, settings: &Settings) -> Result<OwnershipReport> {
+    let db = launch_services_db::read_launch_services_database()?;
+    let handlers = launch_services_db::handlers_for_bundle_id(&db, &app_info.bundle_id);
+    debug!(
+        "{} handler entries name {}",
+        handlers.len(),
+        app_info.bundle_id
+    );
+
+    let capabilities = Capabilities::of(&*BundleInfo::load(&app_info.path)?);
+    Ok(OwnershipReport::new(
+        handlers,
+        &capabilities,
+        &*UtiGraph::installed(&settings.search_paths())?,
+    ))
+}
+
 /// Get the bundle ID for an application
-pub fn get_app_bundle_id(app_name_or_path: &str) -> Result<String> {
+pub fn get_app_bundle_id(app_name_or_path: &str, settings: &Settings) -> Result<String> {
     debug!("Getting bundle ID for: {}", app_name_or_path);
 
     if app_name_or_path.contains('.') && !app_name_or_path.contains('/') {
@@ -98,100 +137,32 @@ pub fn get_app_bundle_id(app_name_or_path: &str) -> Result<String> {
         return Ok(app_name_or_path.to_string());
     }
 
-    workspace::resolve_to_bundle_id(app_name_or_path)
+    workspace::resolve_to_bundle_id(app_name_or_path, settings)
 }
 
 /// Get the version of an application
-pub fn get_app_version(app_name_or_bundle_id: &str) -> Result<String> {
-    let app_info = get_app_info(app_name_or_bundle_id)?;
+pub fn get_app_version(app_name_or_bundle_id: &str, settings: &Settings) -> Result<String> {
+    let app_info = get_app_info(app_name_or_bundle_id, settings)?;
     Ok(app_info.version)
 }
 
 /// Find application paths for a bundle identifier
-pub fn get_app_paths_for_bundle_id(bundle_id: &str) -> Result<Vec<PathBuf>> {
-    workspace::get_app_paths_for_bundle_id(bundle_id)
-}
-
-fn parse_document_types(info_dict: &plist::Dictionary) -> Vec<DeclaredType> {
-    let mut declared_types = Vec::new();
-
-    if let Some(doc_types) = info_dict
-        .get("CFBundleDocumentTypes")
-        .and_then(|v| v.as_array())
-    {
-        for doc_type in doc_types {
-            if let Some(type_dict) = doc_type.as_dictionary() {
-                let name = type_dict
-                    .get("CFBundleTypeName")
-                    .and_then(|v| v.as_string())
-                    .unwrap_or("Unknown Type")
-                    .to_string();
-
-                let description = type_dict
-                    .get("CFBundleTypeDescription")
-                    .and_then(|v| v.as_string())
-                    .map(|s| s.to_string());
-
-                // Get UTIs
-                let utis = if let Some(uti_array) = type_dict
-                    .get("LSItemContentTypes")
-                    .and_then(|v| v.as_array())
-                {
-                    uti_array
-                        .iter()
-                        .filter_map(|v| v.as_string())
-                        .map(|s| s.to_string())
-                        .collect()
-                } else {
-                    Vec::new()
-                };
-
-                // Get file extensions
-                let extensions = if let Some(ext_array) = type_dict
-                    .get("CFBundleTypeExtensions")
-                    .and_then(|v| v.as_array())
-                {
-                    ext_array
-                        .iter()
-                        .filter_map(|v| v.as_string())
-                        .map(|s| s.to_string())
-                        .collect()
-                } else {
-                    Vec::new()
-                };
-
-                declared_types.push(DeclaredType {
-                    name,
-                    utis,
-                    extensions,
-                    description,
-                });
-            }
-        }
-    }
-
-    declared_types
+pub fn get_app_paths_for_bundle_id(bundle_id: &str, settings: &Settings) -> Result<Vec<PathBuf>> {
+    workspace::get_app_paths_for_bundle_id(bundle_id, settings)
 }
 
-fn parse_url_schemes(info_dict: &plist::Dictionary) -> Vec<String> {
-    let mut schemes = Vec::new();
-
-    if let Some(url_types) = info_dict.get("CFBundleURLTypes").and_then(|v| v.as_array()) {
-        for url_type in url_types {
-            if let Some(type_dict) = url_type.as_dictionary() {
-                if let Some(scheme_array) = type_dict
-                    .get("CFBundleURLSchemes")
-                    .and_then(|v| v.as_array())
-                {
-                    for scheme_value in scheme_array {
-                        if let Some(scheme) = scheme_value.as_string() {
-                            schemes.push(scheme.to_string());
-                        }
-                    }
-                }
-            }
+impl From<&DocumentType> for DeclaredType {
+    fn from(document_type: &DocumentType) -> Self {
+        Self {
+            name: document_type
+                .name
+                .clone()
+                .unwrap_or_else(|| "Unknown Type".to_string()),
+            utis: document_type.content_types.clone(),
+            extensions: document_type.extensions.clone(),
+            description: document_type.description.clone(),
+            rank: document_type.rank,
+            role: document_type.role,
         }
     }
-
-    schemes
 }
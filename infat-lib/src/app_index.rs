@@ -0,0 +1,1095 @@
+//! Persistent index of installed application bundles
+//!
+//! Walking the application directories and reading every `Info.plist` is the
+//! slowest part of resolving an app, so it happens once and the result is cached
+//! on disk. The cache records the modification time of every directory it walked
+//! (and of every `Info.plist` it read) and is rebuilt as soon as any of them changes.
+//!
+//! Apps are found by name with [`AppIndex::find_by_name`], which tries each of
+//! an app's names in turn, case-insensitively and with or without `.app`:
+//!
+//! 1. the bundle's file name, as Finder shows it (`Visual Studio Code`)
+//! 2. `CFBundleDisplayName`
+//! 3. `CFBundleName` (`Code`)
+//! 4. `CFBundleExecutable` (`Electron`)
+//! 5. a localized display or bundle name from any `*.lproj/InfoPlist.strings`
+//!    (`Vorschau` for Preview)
+//!
+//! The first kind of name that matches anything decides. If it matches more
+//! than one app, the name is ambiguous and every candidate is reported rather
+//! than one being picked. Copies of the same bundle ID count as one app.
+//!
+//! When one bundle ID is installed more than once (a release and a beta build,
+//! a copy on a mounted disk image), every lookup settles on the same copy:
+//!
+//! 1. a path the user gave explicitly
+//! 2. a copy inside `/Applications`
+//! 3. the highest version
+//! 4. the first copy in search path order
+//!
+//! With [`CopyPolicy::Newest`] the highest version comes before `/Applications`.
+
+use crate::{
+    bundle::{self, BundleInfo},
+    discovery::{self, SearchPath, WalkOptions},
+    error::{InfatError, Result},
+    localization,
+    uti::TypeDeclaration,
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::{debug, info, warn};
+
+/// Bump whenever the cached layout changes so old caches are discarded
+const CACHE_VERSION: u32 = 8;
+
+/// Index shared by every lookup in this process, with the search paths it
+/// was built from
+static SHARED: Mutex<Option<(Vec<SearchPath>, Arc<AppIndex>)>> = Mutex::new(None);
+
+/// Environment variable overriding the search paths, `:`-separated like `PATH`
+pub const APP_PATH_VAR: &str = "INFAT_APP_PATH";
+
+/// An application bundle found while scanning the search paths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedApp {
+    /// Location of the `.app` bundle
+    pub path: PathBuf,
+
+    /// File stem of the bundle, e.g. `Safari` for `Safari.app`
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+
+    /// `CFBundleDisplayName`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// `CFBundleName`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_name: Option<String>,
+
+    /// `CFBundleExecutable`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<String>,
+
+    /// Names from the bundle's localizations that differ from the ones above
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub localized_names: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// `UTExportedTypeDeclarations`: types the app defines
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exported_types: Vec<TypeDeclaration>,
+
+    /// `UTImportedTypeDeclarations`: types the app uses and declares in case nobody else does
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imported_types: Vec<TypeDeclaration>,
+
+    /// URL schemes from `CFBundleURLTypes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub url_schemes: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    info_modified: Option<SystemTime>,
+
+    /// When the localized names were last changed; see [`localization::modified`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    strings_modified: Option<SystemTime>,
+}
+
+impl IndexedApp {
+    /// Read an application bundle the index doesn't cover, e.g. one Launch
+    /// Services knows about outside the search paths
+    pub fn read(path: PathBuf) -> Self {
+        index_app(path)
+    }
+
+    /// The friendliest available name, as Finder would show it
+    pub fn preferred_name(&self) -> &str {
+        self.display_name
+            .as_deref()
+            .or(self.bundle_name.as_deref())
+            .unwrap_or(&self.name)
+    }
+}
+
+/// The kinds of name an app can be found by, in the order they're tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameKind {
+    FileName,
+    DisplayName,
+    BundleName,
+    Executable,
+    Localized,
+}
+
+impl NameKind {
+    const ALL: [Self; 5] = [
+        Self::FileName,
+        Self::DisplayName,
+        Self::BundleName,
+        Self::Executable,
+        Self::Localized,
+    ];
+
+    fn of(self, app: &IndexedApp) -> Vec<&str> {
+        match self {
+            Self::FileName => vec![&app.name],
+            Self::DisplayName => app.display_name.as_deref().into_iter().collect(),
+            Self::BundleName => app.bundle_name.as_deref().into_iter().collect(),
+            Self::Executable => app.executable.as_deref().into_iter().collect(),
+            Self::Localized => app.localized_names.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for NameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::FileName => "file name",
+            Self::DisplayName => "display name",
+            Self::BundleName => "bundle name",
+            Self::Executable => "executable name",
+            Self::Localized => "localized name",
+        })
+    }
+}
+
+/// One of the apps an ambiguous name could refer to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub name: String,
+    pub path: PathBuf,
+    pub bundle_id: Option<String>,
+}
+
+/// Every app an ambiguous name matched, shown one per line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Candidates(pub Vec<Candidate>);
+
+impl std::fmt::Display for Candidates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for candidate in &self.0 {
+            write!(
+                f,
+                "\n  {} ({}) at {}",
+                candidate.name,
+                candidate.bundle_id.as_deref().unwrap_or("no bundle ID"),
+                candidate.path.display()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Modification time of a directory at the moment it was scanned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirectoryStamp {
+    path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<SystemTime>,
+}
+
+impl DirectoryStamp {
+    fn capture(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified_time(path),
+        }
+    }
+
+    fn is_current(&self) -> bool {
+        modified_time(&self.path) == self.modified
+    }
+}
+
+/// Lookup tables derived from the app list, rebuilt after loading
+#[derive(Debug, Default)]
+struct Lookup {
+    by_name: [HashMap<String, Vec<usize>>; NameKind::ALL.len()],
+    by_bundle_id: HashMap<String, Vec<usize>>,
+    by_path: HashMap<PathBuf, usize>,
+}
+
+/// Every application found in the search paths, keyed by name, display name,
+/// bundle ID and path
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AppIndex {
+    version: u32,
+    roots: Vec<SearchPath>,
+    options: WalkOptions,
+    directories: Vec<DirectoryStamp>,
+    apps: Vec<IndexedApp>,
+
+    #[serde(skip)]
+    lookup: Lookup,
+}
+
+impl AppIndex {
+    /// Scan the given search paths and build a fresh index
+    pub fn build(roots: &[PathBuf]) -> Self {
+        let roots: Vec<SearchPath> = roots.iter().cloned().map(SearchPath::from).collect();
+        Self::build_with(&roots, &WalkOptions::default())
+    }
+
+    /// Scan the given search paths with explicit walk options
+    pub fn build_with(roots: &[SearchPath], options: &WalkOptions) -> Self {
+        debug!(
+            "Building application index from {} search paths",
+            roots.len()
+        );
+
+        let (directories, apps) = options.install(|| {
+            let walk = discovery::walk_in_current_pool(roots, options);
+
+            // Missing roots are stamped too, so the index notices when they appear
+            let directories: Vec<DirectoryStamp> = walk
+                .directories
+                .par_iter()
+                .map(|dir| DirectoryStamp::capture(dir))
+                .collect();
+
+            let apps: Vec<IndexedApp> = walk.apps.into_par_iter().map(index_app).collect();
+
+            (directories, apps)
+        });
+
+        info!("Indexed {} applications", apps.len());
+
+        let mut index = Self {
+            version: CACHE_VERSION,
+            roots: roots.to_vec(),
+            options: options.clone(),
+            directories,
+            apps,
+            lookup: Lookup::default(),
+        };
+        index.rebuild_lookup();
+        index
+    }
+
+    /// Load a previously saved index, returning `None` if it is missing or unreadable
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return None;
+        }
+
+        match plist::from_file::<_, Self>(path) {
+            Ok(mut index) if index.version == CACHE_VERSION => {
+                index.rebuild_lookup();
+                Some(index)
+            }
+            Ok(_) => {
+                debug!("Discarding application index with outdated format");
+                None
+            }
+            Err(e) => {
+                debug!("Could not read application index {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Write the index to disk
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        plist::to_file_binary(path, self).map_err(|e| InfatError::Generic {
+            message: format!("Failed to write application index {}: {e}", path.display()),
+        })
+    }
+
+    /// Whether the index still reflects the given search paths on disk
+    pub fn is_fresh(&self, roots: &[SearchPath], options: &WalkOptions) -> bool {
+        if self.roots != roots || self.options != *options {
+            return false;
+        }
+
+        self.directories.iter().all(DirectoryStamp::is_current)
+            && self.apps.iter().all(|app| {
+                modified_time(&bundle::info_plist_path(&app.path)) == app.info_modified
+                    && localization::modified(&app.path) == app.strings_modified
+            })
+    }
+
+    /// All indexed applications
+    pub fn apps(&self) -> &[IndexedApp] {
+        &self.apps
+    }
+
+    /// Paths of all indexed applications
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.apps.iter().map(|app| app.path.clone()).collect()
+    }
+
+    /// Find an app by any of its names, in the order described in the module
+    /// docs. Fails if the first kind of name that matches picks out several apps.
+    pub fn find_by_name(&self, name: &str, policy: CopyPolicy) -> Result<Option<&IndexedApp>> {
+        let key = name.trim();
+        let key = key.strip_suffix(".app").unwrap_or(key).to_lowercase();
+
+        for (kind, names) in NameKind::ALL.iter().zip(&self.lookup.by_name) {
+            let Some(indices) = names.get(&key) else {
+                continue;
+            };
+
+            // Further copies of an app already seen don't make the name ambiguous
+            let mut matches: Vec<&IndexedApp> = Vec::new();
+            for app in indices.iter().map(|&i| &self.apps[i]) {
+                let seen = matches
+                    .iter()
+                    .any(|other| match (&other.bundle_id, &app.bundle_id) {
+                        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+                        _ => other.path == app.path,
+                    });
+                if !seen {
+                    matches.push(app);
+                }
+            }
+
+            if let [app] = matches[..] {
+                debug!("Found '{}' by {}: {}", name, kind, app.path.display());
+                let preferred = app
+                    .bundle_id
+                    .as_deref()
+                    .and_then(|bundle_id| self.find_by_bundle_id(bundle_id, policy));
+                return Ok(Some(preferred.unwrap_or(app)));
+            }
+
+            return Err(InfatError::AmbiguousApplication {
+                name: name.to_string(),
+                candidates: Candidates(
+                    matches
+                        .into_iter()
+                        .map(|app| Candidate {
+                            name: app.preferred_name().to_string(),
+                            path: app.path.clone(),
+                            bundle_id: app.bundle_id.clone(),
+                        })
+                        .collect(),
+                ),
+            });
+        }
+
+        Ok(None)
+    }
+
+    /// Find an app by bundle identifier (case-insensitive, as Launch Services treats them)
+    /// Returns the preferred copy when several are installed.
+    pub fn find_by_bundle_id(&self, bundle_id: &str, policy: CopyPolicy) -> Option<&IndexedApp> {
+        self.copies(bundle_id, policy).into_iter().next()
+    }
+
+    /// Every indexed copy of a bundle ID, preferred copy first
+    pub fn copies(&self, bundle_id: &str, policy: CopyPolicy) -> Vec<&IndexedApp> {
+        let mut copies: Vec<&IndexedApp> = self
+            .lookup
+            .by_bundle_id
+            .get(&bundle_id.to_lowercase())
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.apps[i])
+            .collect();
+        copies.sort_by(|a, b| compare_copies(a, b, None, policy));
+        copies
+    }
+
+    /// Find an app by the path of its bundle
+    pub fn find_by_path<P: AsRef<Path>>(&self, path: P) -> Option<&IndexedApp> {
+        self.lookup
+            .by_path
+            .get(path.as_ref())
+            .map(|&i| &self.apps[i])
+    }
+
+    fn rebuild_lookup(&mut self) {
+        let mut lookup = Lookup::default();
+
+        for (i, app) in self.apps.iter().enumerate() {
+            for (kind, names) in NameKind::ALL.iter().zip(&mut lookup.by_name) {
+                for name in kind.of(app).into_iter().filter(|name| !name.is_empty()) {
+                    names.entry(name.to_lowercase()).or_default().push(i);
+                }
+            }
+
+            if let Some(bundle_id) = &app.bundle_id {
+                lookup
+                    .by_bundle_id
+                    .entry(bundle_id.to_lowercase())
+                    .or_default()
+                    .push(i);
+            }
+
+            lookup.by_path.insert(app.path.clone(), i);
+        }
+
+        self.lookup = lookup;
+    }
+}
+
+/// The directories searched for applications by default
+pub fn default_search_paths() -> Vec<PathBuf> {
+    let mut paths = vec![
+        PathBuf::from("/Applications"),
+        PathBuf::from("/System/Applications"),
+        PathBuf::from("/System/Library/CoreServices/Applications"),
+    ];
+
+    if let Some(home) = dirs::home_dir() {
+        paths.push(home.join("Applications"));
+    }
+
+    paths
+}
+
+/// The directories searched for applications: the configured ones, e.g. from
+/// the configuration's `[settings]`, unless `INFAT_APP_PATH` overrides them
+pub fn search_paths(configured: Vec<SearchPath>) -> Vec<SearchPath> {
+    match std::env::var_os(APP_PATH_VAR) {
+        Some(value) if !value.is_empty() => search_paths_from_env(&value, configured),
+        _ => configured,
+    }
+}
+
+/// Parse `INFAT_APP_PATH`. As with `MANPATH`, an empty component (a leading,
+/// trailing or doubled `:`) stands for the paths that would be searched otherwise.
+fn search_paths_from_env(value: &std::ffi::OsStr, configured: Vec<SearchPath>) -> Vec<SearchPath> {
+    let mut paths = Vec::new();
+    let mut configured = Some(configured);
+
+    for component in std::env::split_paths(value) {
+        if component.as_os_str().is_empty() {
+            paths.extend(configured.take().unwrap_or_default());
+        } else {
+            paths.push(SearchPath::new(discovery::expand_home(
+                &component.to_string_lossy(),
+            )));
+        }
+    }
+
+    paths
+}
+
+/// Location of the on-disk index cache
+pub fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("infat").join("apps.plist"))
+}
+
+/// Get the application index for the given search paths, loading it from the
+/// cache or rescanning when stale
+pub fn shared(roots: &[SearchPath]) -> Result<Arc<AppIndex>> {
+    let mut shared = SHARED.lock().map_err(|_| InfatError::Generic {
+        message: "Application index lock was poisoned".to_string(),
+    })?;
+
+    if let Some((built_from, index)) = shared.as_ref() {
+        if built_from == roots {
+            return Ok(Arc::clone(index));
+        }
+    }
+
+    let options = WalkOptions::default();
+    let cache = cache_path();
+
+    let index = match cache.as_deref().and_then(AppIndex::load) {
+        Some(index) if index.is_fresh(roots, &options) => {
+            debug!("Using cached application index");
+            index
+        }
+        _ => {
+            let index = AppIndex::build_with(roots, &options);
+            if let Some(cache) = &cache {
+                if let Err(e) = index.save(cache) {
+                    warn!("Could not cache application index: {}", e);
+                }
+            }
+            index
+        }
+    };
+
+    let index = Arc::new(index);
+    *shared = Some((roots.to_vec(), Arc::clone(&index)));
+    Ok(index)
+}
+
+/// Throw away the cached index so the next lookup rescans the search paths
+pub fn clear_cache() -> Result<()> {
+    debug!("Clearing application index cache");
+
+    if let Ok(mut shared) = SHARED.lock() {
+        *shared = None;
+    }
+
+    if let Some(cache) = cache_path() {
+        if cache.exists() {
+            fs::remove_file(&cache)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Which copy of an app wins when a bundle ID is installed more than once,
+/// after any path given explicitly
+///
+/// ```toml
+/// [settings]
+/// prefer_copy = "newest"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyPolicy {
+    /// A copy inside `/Applications`, then the highest version
+    #[default]
+    Applications,
+    /// The highest version, then a copy inside `/Applications`
+    Newest,
+}
+
+/// Why one copy of an app was chosen over the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyPreference {
+    Explicit,
+    Applications,
+    HighestVersion,
+    SearchOrder,
+}
+
+impl std::fmt::Display for CopyPreference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Explicit => "path given explicitly",
+            Self::Applications => "installed in /Applications",
+            Self::HighestVersion => "highest version",
+            Self::SearchOrder => "found first",
+        })
+    }
+}
+
+/// Order two copies of an app, preferred copy first, following the policy in
+/// the module docs. Sorting is stable, so equal copies keep search path order.
+pub fn compare_copies(
+    a: &IndexedApp,
+    b: &IndexedApp,
+    explicit: Option<&Path>,
+    policy: CopyPolicy,
+) -> Ordering {
+    copy_preference(a, b, explicit, policy)
+        .map(|(ordering, _)| ordering)
+        .unwrap_or(Ordering::Equal)
+}
+
+/// Why the first of a list of copies sorted by [`compare_copies`] beat the
+/// second, or `None` when there is only one
+pub fn preference_of(
+    copies: &[&IndexedApp],
+    explicit: Option<&Path>,
+    policy: CopyPolicy,
+) -> Option<CopyPreference> {
+    match copies {
+        [first, second, ..] => Some(
+            copy_preference(first, second, explicit, policy)
+                .map(|(_, preference)| preference)
+                .unwrap_or(CopyPreference::SearchOrder),
+        ),
+        _ => None,
+    }
+}
+
+/// The first rule that tells two copies apart, and which way it goes
+fn copy_preference(
+    a: &IndexedApp,
+    b: &IndexedApp,
+    explicit: Option<&Path>,
+    policy: CopyPolicy,
+) -> Option<(Ordering, CopyPreference)> {
+    let is_explicit = |app: &IndexedApp| explicit.is_some_and(|path| app.path == path);
+    let in_applications = |app: &IndexedApp| app.path.starts_with("/Applications");
+
+    let explicit = (
+        is_explicit(b).cmp(&is_explicit(a)),
+        CopyPreference::Explicit,
+    );
+    let applications = (
+        in_applications(b).cmp(&in_applications(a)),
+        CopyPreference::Applications,
+    );
+    let version = (
+        compare_versions(b.version.as_deref(), a.version.as_deref()),
+        CopyPreference::HighestVersion,
+    );
+
+    let rules = match policy {
+        CopyPolicy::Applications => [explicit, applications, version],
+        CopyPolicy::Newest => [explicit, version, applications],
+    };
+    rules.into_iter().find(|(ordering, _)| ordering.is_ne())
+}
+
+/// Compare versions like `1.10.2`, `2.0b3` or `1.0 (4521)` component by
+/// component, numerically where they are numbers. A pre-release suffix sorts
+/// before the plain release, and a missing version before any version.
+pub fn compare_versions(a: Option<&str>, b: Option<&str>) -> Ordering {
+    let (a, b) = match (a, b) {
+        (Some(a), Some(b)) => (a, b),
+        (a, b) => return a.is_some().cmp(&b.is_some()),
+    };
+
+    let components = |version: &str| -> Vec<(u64, String)> {
+        version
+            .split(|c: char| c == '.' || c == '-' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| {
+                let part = part.trim_matches(|c| c == '(' || c == ')');
+                let digits =
+                    part.len() - part.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                let number = part[..digits].parse().unwrap_or(0);
+                (number, part[digits..].to_lowercase())
+            })
+            .collect()
+    };
+
+    let (a, b) = (components(a), components(b));
+    for i in 0..a.len().max(b.len()) {
+        let (number_a, suffix_a) = a.get(i).cloned().unwrap_or_default();
+        let (number_b, suffix_b) = b.get(i).cloned().unwrap_or_default();
+
+        let ordering = number_a.cmp(&number_b).then_with(|| {
+            match (suffix_a.is_empty(), suffix_b.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                // `b3` before `b10`: the letters, then what follows as a version
+                (false, false) => {
+                    let split = |suffix: &str| {
+                        let letters = suffix.len()
+                            - suffix
+                                .trim_start_matches(|c: char| !c.is_ascii_digit())
+                                .len();
+                        (suffix[..letters].to_string(), suffix[letters..].to_string())
+                    };
+                    let ((letters_a, rest_a), (letters_b, rest_b)) =
+                        (split(&suffix_a), split(&suffix_b));
+                    letters_a
+                        .cmp(&letters_b)
+                        .then_with(|| compare_versions(Some(&rest_a), Some(&rest_b)))
+                }
+            }
+        });
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+fn index_app(path: PathBuf) -> IndexedApp {
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let info_modified = modified_time(&bundle::info_plist_path(&path));
+    let strings_modified = localization::modified(&path);
+    let info = match BundleInfo::load(&path) {
+        Ok(info) => info,
+        Err(e) => {
+            debug!("Could not read {}: {}", path.display(), e);
+            Default::default()
+        }
+    };
+
+    // Only names that can't already be found another way
+    let mut localized_names: Vec<String> = localization::load(&path)
+        .values()
+        .flat_map(|names| names.names())
+        .filter(|localized| {
+            ![
+                Some(name.as_str()),
+                info.display_name.as_deref(),
+                info.name.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|known| known.eq_ignore_ascii_case(localized))
+        })
+        .map(str::to_string)
+        .collect();
+    localized_names.sort_by_key(|name| name.to_lowercase());
+    localized_names.dedup_by_key(|name| name.to_lowercase());
+
+    IndexedApp {
+        localized_names,
+        name,
+        bundle_id: info.identifier.clone(),
+        display_name: info.display_name.clone(),
+        bundle_name: info.name.clone(),
+        executable: info.executable.clone(),
+        version: info.preferred_version().map(str::to_string),
+        exported_types: info.exported_types.clone(),
+        imported_types: info.imported_types.clone(),
+        url_schemes: info.url_schemes().map(str::to_string).collect(),
+        info_modified,
+        strings_modified,
+        path,
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn app_path_variable_replaces_or_extends() {
+        let configured = vec![SearchPath::new("/Applications")];
+        let paths = |value: &str| -> Vec<PathBuf> {
+            search_paths_from_env(OsStr::new(value), configured.clone())
+                .into_iter()
+                .map(|search_path| search_path.path)
+                .collect()
+        };
+
+        assert_eq!(paths("/opt/apps"), [PathBuf::from("/opt/apps")]);
+        assert_eq!(
+            paths("/opt/apps:"),
+            [PathBuf::from("/opt/apps"), PathBuf::from("/Applications")]
+        );
+        assert_eq!(
+            paths(":/opt/apps"),
+            [PathBuf::from("/Applications"), PathBuf::from("/opt/apps")]
+        );
+    }
+
+    fn install(root: &Path, app: &str, keys: &[(&str, &str)]) {
+        let contents = root.join(app).join("Contents");
+        fs::create_dir_all(&contents).unwrap();
+
+        let info: plist::Dictionary = keys
+            .iter()
+            .map(|(key, value)| (key.to_string(), plist::Value::from(*value)))
+            .collect();
+        plist::to_file_xml(contents.join("Info.plist"), &info).unwrap();
+    }
+
+    /// Move a file or directory's modification time back, so the next change
+    /// is visible however coarse the filesystem's timestamps are
+    fn backdate(path: &Path) {
+        let time = SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::open(path).unwrap().set_modified(time).unwrap();
+    }
+
+    #[test]
+    fn reuses_a_saved_index_until_something_changes() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let apps = tmp.path().join("Applications");
+        install(
+            &apps,
+            "Editor.app",
+            &[("CFBundleIdentifier", "com.example.editor")],
+        );
+        let info_plist = bundle::info_plist_path(&apps.join("Editor.app"));
+        backdate(&info_plist);
+        backdate(&apps);
+
+        let roots = [SearchPath::new(&apps)];
+        let options = WalkOptions::default();
+        let cache = tmp.path().join("cache").join("apps.plist");
+        AppIndex::build_with(&roots, &options).save(&cache).unwrap();
+
+        let loaded = AppIndex::load(&cache).expect("saved index should load");
+        assert!(loaded.is_fresh(&roots, &options));
+        assert_eq!(
+            loaded
+                .find_by_bundle_id("com.example.editor", CopyPolicy::default())
+                .map(|app| app.name.as_str()),
+            Some("Editor")
+        );
+        assert!(!loaded.is_fresh(&[SearchPath::new(tmp.path())], &options));
+
+        // A new app changes the directory it was added to
+        install(&apps, "Viewer.app", &[]);
+        assert!(!loaded.is_fresh(&roots, &options));
+
+        AppIndex::build_with(&roots, &options).save(&cache).unwrap();
+        let loaded = AppIndex::load(&cache).unwrap();
+        assert!(loaded.is_fresh(&roots, &options));
+
+        // And adding or editing a localized name
+        let resources = apps.join("Editor.app").join("Contents").join("Resources");
+        let lproj = resources.join("de.lproj");
+        fs::create_dir_all(&lproj).unwrap();
+        let strings = lproj.join("InfoPlist.strings");
+        fs::write(&strings, "CFBundleName = \"Bearbeiter\";").unwrap();
+        assert!(!loaded.is_fresh(&roots, &options));
+
+        for path in [&strings, &lproj, &resources] {
+            backdate(path);
+        }
+        AppIndex::build_with(&roots, &options).save(&cache).unwrap();
+        let loaded = AppIndex::load(&cache).unwrap();
+        assert!(loaded.is_fresh(&roots, &options));
+
+        fs::write(&strings, "CFBundleName = \"Editor\";").unwrap();
+        assert!(!loaded.is_fresh(&roots, &options));
+
+        // So does rewriting an Info.plist in place
+        install(
+            &apps,
+            "Editor.app",
+            &[("CFBundleIdentifier", "com.example.editor2")],
+        );
+        assert!(!loaded.is_fresh(&roots, &options));
+    }
+
+    #[test]
+    fn discards_an_index_from_another_version() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cache = tmp.path().join("apps.plist");
+
+        let mut index = AppIndex::build(&[tmp.path().to_path_buf()]);
+        index.version = CACHE_VERSION - 1;
+        index.save(&cache).unwrap();
+
+        assert!(AppIndex::load(&cache).is_none());
+    }
+
+    #[test]
+    fn finds_apps_by_each_name_in_order() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        install(
+            tmp.path(),
+            "Visual Studio Code.app",
+            &[
+                ("CFBundleIdentifier", "com.microsoft.VSCode"),
+                ("CFBundleDisplayName", "Code"),
+                ("CFBundleName", "Code"),
+                ("CFBundleExecutable", "Electron"),
+            ],
+        );
+        install(
+            tmp.path(),
+            "Electron.app",
+            &[("CFBundleIdentifier", "com.github.Electron")],
+        );
+        install(
+            tmp.path(),
+            "Copies/Visual Studio Code.app",
+            &[("CFBundleIdentifier", "com.microsoft.VSCode")],
+        );
+
+        let index = AppIndex::build(&[tmp.path().to_path_buf()]);
+        let bundle_id = |name: &str| {
+            index
+                .find_by_name(name, CopyPolicy::default())
+                .unwrap()
+                .and_then(|app| app.bundle_id.as_deref())
+        };
+
+        // A second copy of the same app isn't ambiguous
+        assert_eq!(
+            bundle_id("visual studio code.app"),
+            Some("com.microsoft.VSCode")
+        );
+        assert_eq!(bundle_id("CODE"), Some("com.microsoft.VSCode"));
+        // The file name is tried before the executable
+        assert_eq!(bundle_id("Electron"), Some("com.github.Electron"));
+        assert_eq!(bundle_id("Atom"), None);
+    }
+
+    #[test]
+    fn finds_apps_by_localized_name() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        install(
+            tmp.path(),
+            "Preview.app",
+            &[
+                ("CFBundleIdentifier", "com.apple.Preview"),
+                ("CFBundleName", "Preview"),
+            ],
+        );
+        let resources = tmp.path().join("Preview.app/Contents/Resources");
+        for (folder, name) in [("de", "Vorschau"), ("fr", "Aperçu"), ("en", "Preview")] {
+            let folder = resources.join(format!("{folder}.lproj"));
+            fs::create_dir_all(&folder).unwrap();
+            let strings: Vec<u8> = format!("\"CFBundleName\" = \"{name}\";")
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect();
+            fs::write(
+                folder.join("InfoPlist.strings"),
+                [&[0xFF, 0xFE][..], &strings].concat(),
+            )
+            .unwrap();
+        }
+
+        let index = AppIndex::build(&[tmp.path().to_path_buf()]);
+        assert_eq!(index.apps()[0].localized_names, ["Aperçu", "Vorschau"]);
+
+        let found = index
+            .find_by_name("vorschau", CopyPolicy::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.bundle_id.as_deref(), Some("com.apple.Preview"));
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        let cmp = |a: &str, b: &str| compare_versions(Some(a), Some(b));
+
+        assert_eq!(cmp("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(cmp("2.0", "2"), Ordering::Equal);
+        assert_eq!(cmp("2.0b3", "2.0"), Ordering::Less);
+        assert_eq!(cmp("2.0b3", "2.0b10"), Ordering::Less);
+        assert_eq!(cmp("1.0 (4521)", "1.0 (4600)"), Ordering::Less);
+        assert_eq!(compare_versions(None, Some("0.1")), Ordering::Less);
+    }
+
+    #[test]
+    fn prefers_one_copy_of_an_app() {
+        let copy = |path: &str, version: Option<&str>| IndexedApp {
+            path: PathBuf::from(path),
+            name: "Zed".into(),
+            bundle_id: Some("dev.zed.Zed".into()),
+            display_name: None,
+            bundle_name: None,
+            executable: None,
+            localized_names: Vec::new(),
+            version: version.map(str::to_string),
+            exported_types: Vec::new(),
+            imported_types: Vec::new(),
+            url_schemes: Vec::new(),
+            info_modified: None,
+            strings_modified: None,
+        };
+        let preview = copy("/Volumes/Zed/Zed.app", Some("0.160.1"));
+        let installed = copy("/Applications/Zed.app", Some("0.150.0"));
+        let beta = copy("/Users/me/Applications/Zed.app", Some("0.161.0-pre"));
+
+        let sorted = |explicit: Option<&Path>, policy: CopyPolicy| {
+            let mut copies = vec![&preview, &beta, &installed];
+            copies.sort_by(|a, b| compare_copies(a, b, explicit, policy));
+            let preference = preference_of(&copies, explicit, policy);
+            (copies[0].path.clone(), copies[1].path.clone(), preference)
+        };
+
+        assert_eq!(
+            sorted(None, CopyPolicy::Applications),
+            (
+                installed.path.clone(),
+                beta.path.clone(),
+                Some(CopyPreference::Applications)
+            )
+        );
+        assert_eq!(
+            sorted(Some(&preview.path), CopyPolicy::Applications),
+            (
+                preview.path.clone(),
+                installed.path.clone(),
+                Some(CopyPreference::Explicit)
+            )
+        );
+
+        let mut elsewhere = vec![&preview, &beta];
+        elsewhere.sort_by(|a, b| compare_copies(a, b, None, CopyPolicy::Applications));
+        assert_eq!(elsewhere[0].path, beta.path);
+        assert_eq!(
+            preference_of(&elsewhere, None, CopyPolicy::Applications),
+            Some(CopyPreference::HighestVersion)
+        );
+
+        // Preferring the newest copy passes over the one in /Applications
+        assert_eq!(
+            sorted(None, CopyPolicy::Newest),
+            (
+                beta.path.clone(),
+                preview.path.clone(),
+                Some(CopyPreference::HighestVersion)
+            )
+        );
+        assert_eq!(
+            sorted(Some(&installed.path), CopyPolicy::Newest).0,
+            installed.path
+        );
+    }
+
+    #[test]
+    fn reports_every_candidate_for_ambiguous_names() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        install(
+            tmp.path(),
+            "Notes.app",
+            &[
+                ("CFBundleIdentifier", "com.example.notes"),
+                ("CFBundleDisplayName", "Scratch"),
+            ],
+        );
+        install(
+            tmp.path(),
+            "Scratchpad.app",
+            &[
+                ("CFBundleIdentifier", "org.example.scratchpad"),
+                ("CFBundleDisplayName", "Scratch"),
+            ],
+        );
+
+        let index = AppIndex::build(&[tmp.path().to_path_buf()]);
+        match index.find_by_name("Scratch", CopyPolicy::default()) {
+            Err(InfatError::AmbiguousApplication { candidates, .. }) => {
+                let mut ids: Vec<_> = candidates
+                    .0
+                    .iter()
+                    .filter_map(|candidate| candidate.bundle_id.as_deref())
+                    .collect();
+                ids.sort();
+                assert_eq!(ids, ["com.example.notes", "org.example.scratchpad"]);
+            }
+            other => panic!("expected an ambiguity error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn search_paths_have_their_own_limits() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        for app in [
+            "Top.app",
+            "Vendor/Nested.app",
+            "Vendor/Deep/Deeper.app",
+            "Old/Legacy.app",
+        ] {
+            fs::create_dir_all(tmp.path().join(app)).unwrap();
+        }
+
+        let root = SearchPath {
+            path: tmp.path().to_path_buf(),
+            max_depth: Some(1),
+            exclude: vec!["Old".into()],
+        };
+        let index = AppIndex::build_with(&[root], &WalkOptions::default());
+
+        let mut names: Vec<_> = index.apps().iter().map(|app| app.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, ["Nested", "Top"]);
+    }
+}
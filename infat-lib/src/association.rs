@@ -1,35 +1,48 @@
 use crate::{
+    bundle::BundleInfo,
+    capability::{Capabilities, Claim, Enforcement},
+    config::{Config, Settings},
+    discovery::SearchPath,
     error::{InfatError, Result},
     macos::{launch_services, workspace},
-    uti::SuperType,
+    pattern::{self, FilePattern},
+    suggest::Suggestions,
+    uti::{DynamicUti, SuperType, TypeDeclaration, TypeTags, UtiGraph},
 };
+use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 /// Set the default application for a file extension
-pub fn set_default_app_for_extension(extension: &str, app_name: &str) -> Result<()> {
+pub fn set_default_app_for_extension(
+    extension: &str,
+    app_name: &str,
+    config: &Config,
+    enforcement: Enforcement,
+) -> Result<()> {
     info!(
         "Setting default app for extension .{} to {}",
         extension, app_name
     );
 
-    // Handle special routing for HTML
-    if extension.to_lowercase() == "html" {
-        debug!("Routing .html to HTTP scheme handler");
-        return set_default_app_for_url_scheme("http", app_name);
-    }
-
-    // Get the UTI for the extension
-    let supertype: Result<SuperType> = extension.parse();
-
-    let uti = match supertype {
-        Ok(val) => val.uti_string().to_string(),
-        Err(_) => launch_services::get_uti_for_extension(extension)?,
+    let uti = match extension_target(extension, config)? {
+        HandlerTarget::UrlScheme(scheme) => {
+            return set_default_app_for_url_scheme(&scheme, app_name, config, enforcement);
+        }
+        HandlerTarget::ContentType(uti) => uti,
     };
-    debug!("Extension .{} maps to UTI: {}", extension, uti);
 
-    // Resolve app name to bundle ID
-    let bundle_id = workspace::resolve_to_bundle_id(app_name)?;
-    debug!("Resolved app '{}' to bundle ID: {}", app_name, bundle_id);
+    let (app_path, bundle_id) = resolve_app(app_name, &config.settings)?;
+
+    check_capability(
+        app_name,
+        &app_path,
+        &[
+            Claim::Extension(pattern::normalize_extension(extension)),
+            Claim::Type(uti.clone()),
+        ],
+        &config.settings,
+        enforcement,
+    )?;
 
     // Set the default app for the UTI
     launch_services::set_default_app_for_uti(&uti, &bundle_id)?;
@@ -37,29 +50,48 @@ pub fn set_default_app_for_extension(extension: &str, app_name: &str) -> Result<
     Ok(())
 }
 
+/// Set the default application for files matching a `[files]` pattern
+pub fn set_default_app_for_file(
+    file_pattern: &str,
+    app_name: &str,
+    config: &Config,
+    enforcement: Enforcement,
+) -> Result<()> {
+    info!(
+        "Setting default app for files matching {} to {}",
+        file_pattern, app_name
+    );
+
+    let pattern = FilePattern::parse(file_pattern);
+    set_default_app_for_extension(pattern.extension()?, app_name, config, enforcement)
+}
+
 /// Set the default application for a URL scheme
-pub fn set_default_app_for_url_scheme(scheme: &str, app_name: &str) -> Result<()> {
+pub fn set_default_app_for_url_scheme(
+    scheme: &str,
+    app_name: &str,
+    config: &Config,
+    enforcement: Enforcement,
+) -> Result<()> {
     info!(
         "Setting default app for URL scheme {} to {}",
         scheme, app_name
     );
 
-    // Handle HTTPS routing to HTTP
-    let actual_scheme = if scheme.to_lowercase() == "https" {
-        debug!("Routing HTTPS to HTTP scheme handler");
-        "http"
-    } else {
-        scheme
-    };
+    let actual_scheme = canonical_scheme(scheme);
 
-    // Resolve app name to bundle ID
-    let bundle_id = workspace::resolve_to_bundle_id(app_name)?;
-    debug!("Resolved app '{}' to bundle ID: {}", app_name, bundle_id);
+    let (app_path, bundle_id) = resolve_app(app_name, &config.settings)?;
+
+    check_capability(
+        app_name,
+        &app_path,
+        &[Claim::Scheme(actual_scheme.to_string())],
+        &config.settings,
+        enforcement,
+    )?;
 
     // Register the application first to ensure it's known to Launch Services
-    if let Some(app_path) = workspace::find_application(app_name)? {
-        launch_services::register_application(&app_path)?;
-    }
+    launch_services::register_application(&app_path)?;
 
     // Set the URL scheme handler
     launch_services::set_default_app_for_url_scheme(actual_scheme, &bundle_id)?;
@@ -68,28 +100,241 @@ pub fn set_default_app_for_url_scheme(scheme: &str, app_name: &str) -> Result<()
 }
 
 /// Set the default application for a supertype/UTI
-pub fn set_default_app_for_type(type_name: &str, app_name: &str) -> Result<()> {
+pub fn set_default_app_for_type(
+    type_name: &str,
+    app_name: &str,
+    config: &Config,
+    enforcement: Enforcement,
+) -> Result<()> {
     info!("Setting default app for type {} to {}", type_name, app_name);
 
+    let uti = match type_target(type_name, config)? {
+        HandlerTarget::UrlScheme(scheme) => {
+            return set_default_app_for_url_scheme(&scheme, app_name, config, enforcement);
+        }
+        HandlerTarget::ContentType(uti) => uti,
+    };
+
+    let (app_path, bundle_id) = resolve_app(app_name, &config.settings)?;
+
+    check_capability(
+        app_name,
+        &app_path,
+        &[Claim::Type(uti.clone())],
+        &config.settings,
+        enforcement,
+    )?;
+
+    // Set the default app for the UTI
+    launch_services::set_default_app_for_uti(&uti, &bundle_id)?;
+
+    Ok(())
+}
+
+/// Find the app once, for both its bundle ID and the declarations it's checked against
+fn resolve_app(app_name: &str, settings: &Settings) -> Result<(PathBuf, String)> {
+    let app_path = workspace::resolve_application(app_name, settings)?;
+    let bundle_id = workspace::get_bundle_id_from_app_path(&app_path, settings)?;
+    debug!("Resolved app '{}' to bundle ID: {}", app_name, bundle_id);
+    Ok((app_path, bundle_id))
+}
+
+/// Warn, or fail under [`Enforcement::Strict`], when the app doesn't declare
+/// any of the claims it is about to become the default for
+fn check_capability(
+    app_name: &str,
+    app_path: &Path,
+    claims: &[Claim],
+    settings: &Settings,
+    enforcement: Enforcement,
+) -> Result<()> {
+    if enforcement == Enforcement::Off {
+        return Ok(());
+    }
+
+    let info = BundleInfo::load(app_path)?;
+
+    Capabilities::of(&info).require(
+        info.preferred_name().unwrap_or(app_name),
+        claims,
+        &*UtiGraph::installed(&settings.search_paths())?,
+        enforcement,
+    )
+}
+
+/// The Launch Services handler a config entry is stored under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandlerTarget {
+    /// A content type (UTI) handler
+    ContentType(String),
+    /// A URL scheme handler
+    UrlScheme(String),
+}
+
+impl HandlerTarget {
+    /// The bundle ID currently registered for this handler
+    pub fn current_handler(&self) -> Result<Option<String>> {
+        match self {
+            Self::ContentType(uti) => launch_services::get_default_app_for_uti(uti),
+            Self::UrlScheme(scheme) => launch_services::get_default_app_for_url_scheme(scheme),
+        }
+    }
+}
+
+impl std::fmt::Display for HandlerTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ContentType(uti) => write!(f, "{uti}"),
+            Self::UrlScheme(scheme) => write!(f, "{scheme}://"),
+        }
+    }
+}
+
+/// Resolve the handler a file extension is stored under, honouring the
+/// configuration's `[declare]` table
+pub fn extension_target(extension: &str, config: &Config) -> Result<HandlerTarget> {
+    let extension = pattern::normalize_extension(extension);
+    let extension = extension.as_str();
+
+    // Handle special routing for HTML
+    if extension == "html" {
+        debug!("Routing .html to HTTP scheme handler");
+        return Ok(HandlerTarget::UrlScheme("http".to_string()));
+    }
+
+    // Supertype names only apply to `--type` and `[types]`: an extension such
+    // as `url` or `text` must resolve to the type Launch Services knows it by
+    let roots = config.settings.search_paths();
+    let uti = match config.declared(extension) {
+        Some(declared) => {
+            debug!(
+                "Extension .{} declared as {} in config",
+                extension, declared
+            );
+            declared
+        }
+        // Launch Services would only look at the last part of a compound extension
+        None if pattern::compound_suffix(extension).is_some() => {
+            pattern::compound_uti(extension, &*UtiGraph::installed(&roots)?)?
+        }
+        None => uti_for_extension(extension, &roots)?,
+    };
+    debug!("Extension .{} maps to UTI: {}", extension, uti);
+
+    Ok(HandlerTarget::ContentType(uti))
+}
+
+/// Resolve an extension through Launch Services, falling back to the types
+/// declared by installed apps when it only has a dynamic UTI for it
+fn uti_for_extension(extension: &str, roots: &[SearchPath]) -> Result<String> {
+    let system = launch_services::get_uti_for_extension(extension);
+    if matches!(&system, Ok(uti) if !DynamicUti::is_dynamic(uti)) {
+        return system;
+    }
+
+    let graph = UtiGraph::installed(roots)?;
+    match graph.preferred_type_for_extension(extension) {
+        Some(declaration) => {
+            debug!(
+                "Extension .{} resolved through declared type {}",
+                extension, declaration.identifier
+            );
+            Ok(declaration.identifier.clone())
+        }
+        None => system,
+    }
+}
+
+/// The declaration of a UTI, from the system table or an installed app
+fn declaration_for(uti: &str, roots: &[SearchPath]) -> Option<TypeDeclaration> {
+    match UtiGraph::installed(roots) {
+        Ok(graph) => graph.get(uti).cloned(),
+        Err(e) => {
+            debug!("Could not load declared types: {}", e);
+            UtiGraph::system().get(uti).cloned()
+        }
+    }
+}
+
+/// Resolve the handler a `[files]` pattern is stored under
+pub fn file_target(file_pattern: &str, config: &Config) -> Result<HandlerTarget> {
+    extension_target(FilePattern::parse(file_pattern).extension()?, config)
+}
+
+/// Resolve the handler a URL scheme is stored under
+pub fn scheme_target(scheme: &str) -> HandlerTarget {
+    HandlerTarget::UrlScheme(canonical_scheme(scheme).to_string())
+}
+
+fn canonical_scheme(scheme: &str) -> &str {
+    // Handle HTTPS routing to HTTP
+    if scheme.to_lowercase() == "https" {
+        debug!("Routing HTTPS to HTTP scheme handler");
+        return "http";
+    }
+
+    scheme
+}
+
+/// Resolve the handler a supertype or UTI is stored under
+pub fn type_target(type_name: &str, config: &Config) -> Result<HandlerTarget> {
     // Handle special routing for web types
     if type_name == "com.apple.default-app.web-browser" || type_name == "public.html" {
         debug!("Routing web browser type to HTTP scheme handler");
-        return set_default_app_for_url_scheme("http", app_name);
+        return Ok(HandlerTarget::UrlScheme("http".to_string()));
     }
 
+    let uti = type_uti(type_name, &config.settings.search_paths())?;
+    debug!("Type '{}' resolved to UTI: {}", type_name, uti);
+    Ok(HandlerTarget::ContentType(uti))
+}
+
+/// Anything that isn't a supertype must look like a UTI; a bare name such as
+/// `plain_txt` is almost certainly a misspelled supertype
+fn type_uti(type_name: &str, roots: &[SearchPath]) -> Result<String> {
     // Try to parse as a SuperType first
-    let uti = if let Ok(supertype) = type_name.parse::<SuperType>() {
-        supertype.uti_string().to_string()
-    } else {
+    match type_name.parse::<SuperType>() {
+        Ok(supertype) => Ok(supertype.uti_string().to_string()),
         // Assume it's already a UTI string
-        type_name.to_string()
+        Err(_) if type_name.contains('.') => Ok(type_name.to_string()),
+        Err(_) => Err(InfatError::UnsupportedSupertype {
+            name: type_name.to_string(),
+            suggestions: Suggestions::installed_types(type_name, roots),
+        }),
+    }
+}
+
+/// Set the default application for a MIME type
+pub fn set_default_app_for_mime_type(
+    mime_type: &str,
+    app_name: &str,
+    config: &Config,
+    enforcement: Enforcement,
+) -> Result<()> {
+    info!(
+        "Setting default app for MIME type {} to {}",
+        mime_type, app_name
+    );
+
+    let uti = match mime_target(mime_type, config)? {
+        HandlerTarget::UrlScheme(scheme) => {
+            return set_default_app_for_url_scheme(&scheme, app_name, config, enforcement);
+        }
+        HandlerTarget::ContentType(uti) => uti,
     };
 
-    debug!("Type '{}' resolved to UTI: {}", type_name, uti);
+    let (app_path, bundle_id) = resolve_app(app_name, &config.settings)?;
 
-    // Resolve app name to bundle ID
-    let bundle_id = workspace::resolve_to_bundle_id(app_name)?;
-    debug!("Resolved app '{}' to bundle ID: {}", app_name, bundle_id);
+    check_capability(
+        app_name,
+        &app_path,
+        &[
+            Claim::Mime(canonical_mime_type(mime_type)),
+            Claim::Type(uti.clone()),
+        ],
+        &config.settings,
+        enforcement,
+    )?;
 
     // Set the default app for the UTI
     launch_services::set_default_app_for_uti(&uti, &bundle_id)?;
@@ -97,16 +342,104 @@ pub fn set_default_app_for_type(type_name: &str, app_name: &str) -> Result<()> {
     Ok(())
 }
 
-/// Get information about the default app for a file extension
-pub fn get_info_for_extension(extension: &str) -> Result<AssociationInfo> {
+/// Resolve the handler a MIME type is stored under
+pub fn mime_target(mime_type: &str, config: &Config) -> Result<HandlerTarget> {
+    let mime_type = canonical_mime_type(mime_type);
+
+    // HTML is owned by the browser, like .html
+    if mime_type == "text/html" {
+        debug!("Routing text/html to HTTP scheme handler");
+        return Ok(HandlerTarget::UrlScheme("http".to_string()));
+    }
+
+    let uti = uti_for_mime_type(&mime_type, &config.settings.search_paths())?;
+    debug!("MIME type {} maps to UTI: {}", mime_type, uti);
+
+    Ok(HandlerTarget::ContentType(uti))
+}
+
+/// Lowercase a MIME type and drop parameters such as `; charset=utf-8`
+fn canonical_mime_type(mime_type: &str) -> String {
+    mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+}
+
+/// Resolve a MIME type through Launch Services, falling back to the offline
+/// table and the types declared by installed apps
+fn uti_for_mime_type(mime_type: &str, roots: &[SearchPath]) -> Result<String> {
+    let system = launch_services::get_uti_for_mime_type(mime_type);
+    if matches!(&system, Ok(uti) if !DynamicUti::is_dynamic(uti)) {
+        return system;
+    }
+
+    let graph = UtiGraph::installed(roots)?;
+    match graph.preferred_type_for_mime_type(mime_type) {
+        Some(declaration) => {
+            debug!(
+                "MIME type {} resolved through declared type {}",
+                mime_type, declaration.identifier
+            );
+            Ok(declaration.identifier.clone())
+        }
+        None => system,
+    }
+}
+
+/// The preferred MIME type for a UTI, from Launch Services or the offline table
+pub fn mime_type_for_uti(uti: &str, roots: &[SearchPath]) -> Option<String> {
+    match launch_services::get_mime_type_for_uti(uti) {
+        Ok(Some(mime_type)) => Some(mime_type),
+        _ => declaration_for(uti, roots)
+            .and_then(|declaration| declaration.mime_types.first().cloned()),
+    }
+}
+
+/// Every extension, MIME type and OSType that maps to a UTI, from Launch
+/// Services and the offline table
+pub fn tags_for_uti(uti: &str, roots: &[SearchPath]) -> TypeTags {
+    let mut tags = launch_services::get_tags_for_uti(uti).unwrap_or_else(|e| {
+        debug!(
+            "Could not read tags for {} from Launch Services: {}",
+            uti, e
+        );
+        TypeTags::default()
+    });
+
+    if let Some(declaration) = declaration_for(uti, roots) {
+        tags.merge(&declaration.tags());
+    }
+
+    tags
+}
+
+/// Get information about the default app for a file extension, showing the
+/// configuration's `[declare]` override alongside the system mapping
+pub fn get_info_for_extension(extension: &str, config: &Config) -> Result<AssociationInfo> {
+    let extension = &pattern::normalize_extension(extension);
     debug!("Getting info for extension: .{}", extension);
 
-    let uti = launch_services::get_uti_for_extension(extension)?;
+    // A [declare] override replaces the system mapping; keep that around to show both
+    let roots = config.settings.search_paths();
+    let declared = config.declared(extension);
+    let (uti, system_uti) = match &declared {
+        Some(uti) => (uti.clone(), uti_for_extension(extension, &roots).ok()),
+        None => (uti_for_extension(extension, &roots)?, None),
+    };
+
     let default_app = launch_services::get_default_app_for_uti(&uti)?;
     let all_apps = launch_services::get_all_apps_for_uti(&uti)?;
 
     Ok(AssociationInfo {
         identifier: format!(".{extension}"),
+        declared_in_config: declared.is_some(),
+        system_uti,
+        declaration: declaration_for(&uti, &roots),
+        mime_type: mime_type_for_uti(&uti, &roots),
+        tags: tags_for_uti(&uti, &roots),
         uti: Some(uti),
         default_app,
         all_apps,
@@ -122,29 +455,57 @@ pub fn get_info_for_url_scheme(scheme: &str) -> Result<AssociationInfo> {
 
     Ok(AssociationInfo {
         identifier: scheme.to_string(),
+        declared_in_config: false,
+        system_uti: None,
         uti: None,
+        declaration: None,
+        mime_type: None,
+        tags: TypeTags::default(),
         default_app,
         all_apps,
     })
 }
 
 /// Get information about the default app for a UTI/supertype
-pub fn get_info_for_type(type_name: &str) -> Result<AssociationInfo> {
+pub fn get_info_for_type(type_name: &str, config: &Config) -> Result<AssociationInfo> {
     debug!("Getting info for type: {}", type_name);
 
-    // Try to parse as a SuperType first
-    let uti = if let Ok(supertype) = type_name.parse::<SuperType>() {
-        supertype.uti_string().to_string()
-    } else {
-        // Assume it's already a UTI string
-        type_name.to_string()
-    };
+    let roots = config.settings.search_paths();
+    let uti = type_uti(type_name, &roots)?;
 
     let default_app = launch_services::get_default_app_for_uti(&uti)?;
     let all_apps = launch_services::get_all_apps_for_uti(&uti)?;
 
     Ok(AssociationInfo {
         identifier: type_name.to_string(),
+        declared_in_config: false,
+        system_uti: None,
+        declaration: declaration_for(&uti, &roots),
+        mime_type: mime_type_for_uti(&uti, &roots),
+        tags: tags_for_uti(&uti, &roots),
+        uti: Some(uti),
+        default_app,
+        all_apps,
+    })
+}
+
+/// Get information about the default app for a MIME type
+pub fn get_info_for_mime_type(mime_type: &str, config: &Config) -> Result<AssociationInfo> {
+    debug!("Getting info for MIME type: {}", mime_type);
+
+    let roots = config.settings.search_paths();
+    let mime_type = canonical_mime_type(mime_type);
+    let uti = uti_for_mime_type(&mime_type, &roots)?;
+    let default_app = launch_services::get_default_app_for_uti(&uti)?;
+    let all_apps = launch_services::get_all_apps_for_uti(&uti)?;
+
+    Ok(AssociationInfo {
+        declaration: declaration_for(&uti, &roots),
+        identifier: mime_type.clone(),
+        declared_in_config: false,
+        system_uti: None,
+        mime_type: Some(mime_type),
+        tags: tags_for_uti(&uti, &roots),
         uti: Some(uti),
         default_app,
         all_apps,
@@ -156,15 +517,24 @@ pub fn get_info_for_type(type_name: &str) -> Result<AssociationInfo> {
 pub struct AssociationInfo {
     pub identifier: String,
     pub uti: Option<String>,
+    /// Whether the UTI comes from the config's `[declare]` table
+    pub declared_in_config: bool,
+    /// The UTI macOS maps the extension to, when `[declare]` overrides it
+    pub system_uti: Option<String>,
+    /// Where the UTI is declared, if infat knows it
+    pub declaration: Option<TypeDeclaration>,
+    pub mime_type: Option<String>,
+    /// Every tag that maps to the UTI, i.e. everything an association for it affects
+    pub tags: TypeTags,
     pub default_app: Option<String>,
     pub all_apps: Vec<String>,
 }
 
 impl AssociationInfo {
     /// Get the default app name (if available)
-    pub fn default_app_name(&self) -> Result<Option<String>> {
+    pub fn default_app_name(&self, settings: &Settings) -> Result<Option<String>> {
         if let Some(bundle_id) = &self.default_app {
-            match workspace::get_app_name_from_bundle_id(bundle_id) {
+            match workspace::get_app_name_from_bundle_id(bundle_id, settings) {
                 Ok(name) => Ok(Some(name)),
                 Err(InfatError::SystemService { .. }) => Ok(Some(bundle_id.clone())),
                 Err(InfatError::ApplicationNotFound { .. }) => Ok(Some(bundle_id.clone())),
@@ -176,22 +546,22 @@ impl AssociationInfo {
     }
 
     /// Get all app names (with fallback to bundle IDs)
-    pub fn all_app_names(&self) -> Vec<String> {
+    pub fn all_app_names(&self, settings: &Settings) -> Vec<String> {
         self.all_apps
             .iter()
             .map(|bundle_id| {
-                workspace::get_app_name_from_bundle_id(bundle_id)
+                workspace::get_app_name_from_bundle_id(bundle_id, settings)
                     .unwrap_or_else(|_| bundle_id.clone())
             })
             .collect()
     }
 
     /// Get app paths for all registered apps
-    pub fn all_app_paths(&self) -> Vec<String> {
+    pub fn all_app_paths(&self, settings: &Settings) -> Vec<String> {
         self.all_apps
             .iter()
             .filter_map(|bundle_id| {
-                workspace::get_app_paths_for_bundle_id(bundle_id)
+                workspace::get_app_paths_for_bundle_id(bundle_id, settings)
                     .ok()
                     .and_then(|paths| paths.first().cloned())
                     .map(|path| path.display().to_string())
@@ -0,0 +1,479 @@
+//! Typed model of an application bundle's `Info.plist`
+//!
+//! Real-world `Info.plist` files are loose: a list may be written as a single
+//! string, booleans show up as `"YES"` or `1`, and one broken document type
+//! shouldn't hide the rest of the bundle. Fields are therefore parsed leniently,
+//! dropping values of the wrong shape instead of failing the whole file.
+
+use crate::error::{InfatError, InfatErrorExt, Result};
+use crate::uti::TypeDeclaration;
+use plist::Value;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::debug;
+
+/// A parsed `Info.plist` and the modification time it was parsed at
+type CachedInfo = (Option<SystemTime>, Arc<BundleInfo>);
+
+/// Parsed `Info.plist` files, keyed by bundle path
+static CACHE: Mutex<Option<HashMap<PathBuf, CachedInfo>>> = Mutex::new(None);
+
+/// The parts of an `Info.plist` infat cares about
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct BundleInfo {
+    #[serde(rename = "CFBundleIdentifier", default, deserialize_with = "string")]
+    pub identifier: Option<String>,
+
+    #[serde(rename = "CFBundleName", default, deserialize_with = "string")]
+    pub name: Option<String>,
+
+    #[serde(rename = "CFBundleDisplayName", default, deserialize_with = "string")]
+    pub display_name: Option<String>,
+
+    #[serde(rename = "CFBundleExecutable", default, deserialize_with = "string")]
+    pub executable: Option<String>,
+
+    /// The marketing version, e.g. `1.4.2`
+    #[serde(
+        rename = "CFBundleShortVersionString",
+        default,
+        deserialize_with = "string"
+    )]
+    pub short_version: Option<String>,
+
+    /// The build number
+    #[serde(rename = "CFBundleVersion", default, deserialize_with = "string")]
+    pub version: Option<String>,
+
+    #[serde(rename = "CFBundleDocumentTypes", default, deserialize_with = "list")]
+    pub document_types: Vec<DocumentType>,
+
+    #[serde(rename = "CFBundleURLTypes", default, deserialize_with = "list")]
+    pub url_types: Vec<UrlType>,
+
+    /// `UTExportedTypeDeclarations`: types the app defines
+    #[serde(
+        rename = "UTExportedTypeDeclarations",
+        default,
+        deserialize_with = "type_declarations"
+    )]
+    pub exported_types: Vec<TypeDeclaration>,
+
+    /// `UTImportedTypeDeclarations`: types the app uses and declares in case nobody else does
+    #[serde(
+        rename = "UTImportedTypeDeclarations",
+        default,
+        deserialize_with = "type_declarations"
+    )]
+    pub imported_types: Vec<TypeDeclaration>,
+
+    #[serde(
+        rename = "LSMinimumSystemVersion",
+        default,
+        deserialize_with = "string"
+    )]
+    pub minimum_system_version: Option<String>,
+
+    /// Agent app without a Dock icon or menu bar
+    #[serde(rename = "LSUIElement", default, deserialize_with = "flag")]
+    pub ui_element: bool,
+
+    /// Runs without any user interface at all
+    #[serde(rename = "LSBackgroundOnly", default, deserialize_with = "flag")]
+    pub background_only: bool,
+}
+
+/// One entry of `CFBundleDocumentTypes`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct DocumentType {
+    #[serde(rename = "CFBundleTypeName", default, deserialize_with = "string")]
+    pub name: Option<String>,
+
+    /// Not an Apple key, but some apps describe their types with it
+    #[serde(
+        rename = "CFBundleTypeDescription",
+        default,
+        deserialize_with = "string"
+    )]
+    pub description: Option<String>,
+
+    #[serde(rename = "CFBundleTypeRole", default, deserialize_with = "parsed")]
+    pub role: Option<BundleTypeRole>,
+
+    #[serde(rename = "LSHandlerRank", default, deserialize_with = "parsed")]
+    pub rank: Option<HandlerRank>,
+
+    #[serde(rename = "LSItemContentTypes", default, deserialize_with = "strings")]
+    pub content_types: Vec<String>,
+
+    /// Legacy tags, ignored by Launch Services when `LSItemContentTypes` is present
+    #[serde(
+        rename = "CFBundleTypeExtensions",
+        default,
+        deserialize_with = "strings"
+    )]
+    pub extensions: Vec<String>,
+
+    #[serde(
+        rename = "CFBundleTypeMIMETypes",
+        default,
+        deserialize_with = "strings"
+    )]
+    pub mime_types: Vec<String>,
+
+    #[serde(rename = "CFBundleTypeOSTypes", default, deserialize_with = "strings")]
+    pub os_types: Vec<String>,
+
+    /// Documents of this type are directories presented as a single file
+    #[serde(rename = "LSTypeIsPackage", default, deserialize_with = "flag")]
+    pub is_package: bool,
+
+    #[serde(rename = "CFBundleTypeIconFile", default, deserialize_with = "string")]
+    pub icon_file: Option<String>,
+
+    /// Asset catalog icon name
+    #[serde(rename = "CFBundleTypeIconName", default, deserialize_with = "string")]
+    pub icon_name: Option<String>,
+
+    /// Let macOS draw the document icon from the app icon
+    #[serde(
+        rename = "CFBundleTypeIconSystemGenerated",
+        default,
+        deserialize_with = "flag"
+    )]
+    pub icon_system_generated: bool,
+}
+
+/// One entry of `CFBundleURLTypes`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct UrlType {
+    #[serde(rename = "CFBundleURLName", default, deserialize_with = "string")]
+    pub name: Option<String>,
+
+    #[serde(rename = "CFBundleURLSchemes", default, deserialize_with = "strings")]
+    pub schemes: Vec<String>,
+
+    #[serde(rename = "CFBundleTypeRole", default, deserialize_with = "parsed")]
+    pub role: Option<BundleTypeRole>,
+
+    #[serde(rename = "CFBundleURLIconFile", default, deserialize_with = "string")]
+    pub icon_file: Option<String>,
+}
+
+/// What an app can do with a document type (`CFBundleTypeRole`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum BundleTypeRole {
+    Editor,
+    Viewer,
+    Shell,
+    /// Quick Look generator
+    QLGenerator,
+    None,
+}
+
+/// How strongly an app claims a document type (`LSHandlerRank`)
+///
+/// Ordered from the strongest claim to the weakest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum HandlerRank {
+    /// The app created the type
+    Owner,
+    /// The app is a primary handler for the type
+    Default,
+    /// The app can open the type, but shouldn't be preferred
+    Alternate,
+    /// The app should never be picked for the type
+    None,
+}
+
+impl std::str::FromStr for BundleTypeRole {
+    type Err = InfatError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "editor" => Ok(Self::Editor),
+            "viewer" => Ok(Self::Viewer),
+            "shell" => Ok(Self::Shell),
+            "qlgenerator" => Ok(Self::QLGenerator),
+            "none" => Ok(Self::None),
+            _ => Err(InfatError::Generic {
+                message: format!("Unknown CFBundleTypeRole '{s}'"),
+            }),
+        }
+    }
+}
+
+impl std::str::FromStr for HandlerRank {
+    type Err = InfatError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "owner" => Ok(Self::Owner),
+            "default" => Ok(Self::Default),
+            "alternate" => Ok(Self::Alternate),
+            "none" => Ok(Self::None),
+            _ => Err(InfatError::Generic {
+                message: format!("Unknown LSHandlerRank '{s}'"),
+            }),
+        }
+    }
+}
+
+impl std::fmt::Display for BundleTypeRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::fmt::Display for HandlerRank {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl BundleInfo {
+    /// Parse an `Info.plist` in XML or binary form
+    pub fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, plist::Error> {
+        plist::from_bytes(bytes)
+    }
+
+    /// Parse an `Info.plist` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        plist::from_file(path).plist_read_error(path)
+    }
+
+    /// The `Info.plist` of an application bundle, parsed once and cached until
+    /// the file changes
+    pub fn load(app_path: &Path) -> Result<Arc<Self>> {
+        let info_path = info_plist_path(app_path);
+        let modified = std::fs::metadata(&info_path)
+            .map_err(|_| InfatError::InfoPlistNotFound {
+                app_path: app_path.to_path_buf(),
+            })?
+            .modified()
+            .ok();
+
+        if let Ok(cache) = CACHE.lock() {
+            if let Some((parsed_at, info)) = cache.as_ref().and_then(|cache| cache.get(app_path)) {
+                if *parsed_at == modified {
+                    return Ok(Arc::clone(info));
+                }
+            }
+        }
+
+        debug!("Parsing {}", info_path.display());
+        let info = Arc::new(Self::from_file(&info_path)?);
+
+        if let Ok(mut cache) = CACHE.lock() {
+            cache
+                .get_or_insert_with(HashMap::new)
+                .insert(app_path.to_path_buf(), (modified, Arc::clone(&info)));
+        }
+        Ok(info)
+    }
+
+    /// The friendliest name the bundle declares, as Finder would show it
+    pub fn preferred_name(&self) -> Option<&str> {
+        self.display_name.as_deref().or(self.name.as_deref())
+    }
+
+    /// The marketing version, falling back to the build number
+    pub fn preferred_version(&self) -> Option<&str> {
+        self.short_version.as_deref().or(self.version.as_deref())
+    }
+
+    /// Every URL scheme from `CFBundleURLTypes`
+    pub fn url_schemes(&self) -> impl Iterator<Item = &str> {
+        self.url_types
+            .iter()
+            .flat_map(|url_type| url_type.schemes.iter().map(String::as_str))
+    }
+}
+
+/// Location of the `Info.plist` in an application bundle
+pub fn info_plist_path(app_path: &Path) -> PathBuf {
+    app_path.join("Contents").join("Info.plist")
+}
+
+/// A string, or a number written where a string belongs
+fn string<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<String>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => Some(s),
+        Value::Integer(n) => Some(n.to_string()),
+        Value::Real(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// An array of strings, or a single string
+fn strings<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => vec![s],
+        Value::Array(values) => values.into_iter().filter_map(Value::into_string).collect(),
+        _ => Vec::new(),
+    })
+}
+
+/// A boolean, or `YES`/`1`-style strings and integers
+fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<bool, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Boolean(b) => b,
+        Value::Integer(n) => n.as_signed().is_some_and(|n| n != 0),
+        Value::String(s) => matches!(s.trim().to_lowercase().as_str(), "yes" | "true" | "1"),
+        _ => false,
+    })
+}
+
+/// A string naming one of a fixed set of values; anything unknown is dropped
+fn parsed<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+{
+    Ok(string(deserializer)?.and_then(|s| s.parse().ok()))
+}
+
+/// An array of dictionaries, skipping entries that don't parse
+fn list<'de, D, T>(deserializer: D) -> std::result::Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Array(values) => values
+            .into_iter()
+            .filter_map(|value| plist::from_value(&value).ok())
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+fn type_declarations<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<TypeDeclaration>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Array(values) => values
+            .iter()
+            .filter_map(Value::as_dictionary)
+            .filter_map(TypeDeclaration::from_plist)
+            .collect(),
+        _ => Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn parses_an_editor_bundle() {
+        let info = BundleInfo::from_file(&fixture("Editor.plist")).unwrap();
+
+        assert_eq!(info.identifier.as_deref(), Some("com.example.Editor"));
+        assert_eq!(info.preferred_name(), Some("Example Editor"));
+        assert_eq!(info.executable.as_deref(), Some("Editor"));
+        assert_eq!(info.preferred_version(), Some("2.3.1"));
+        assert_eq!(info.version.as_deref(), Some("2301"));
+        assert_eq!(info.minimum_system_version.as_deref(), Some("12.0"));
+        assert!(!info.ui_element && !info.background_only);
+
+        let markdown = &info.document_types[0];
+        assert_eq!(markdown.name.as_deref(), Some("Markdown Document"));
+        assert_eq!(markdown.role, Some(BundleTypeRole::Editor));
+        assert_eq!(markdown.rank, Some(HandlerRank::Owner));
+        assert_eq!(markdown.content_types, ["net.daringfireball.markdown"]);
+        assert_eq!(markdown.icon_file.as_deref(), Some("Markdown.icns"));
+
+        let project = &info.document_types[1];
+        assert_eq!(project.rank, Some(HandlerRank::Alternate));
+        assert_eq!(project.role, Some(BundleTypeRole::Viewer));
+        assert!(project.is_package);
+        assert_eq!(project.extensions, ["edproj"]);
+        assert!(HandlerRank::Owner < HandlerRank::Default);
+        assert!(HandlerRank::Default < HandlerRank::Alternate);
+
+        assert_eq!(
+            info.url_schemes().collect::<Vec<_>>(),
+            ["editor", "x-editor"]
+        );
+        assert_eq!(info.url_types[0].role, Some(BundleTypeRole::Viewer));
+        assert_eq!(
+            info.exported_types[0].identifier,
+            "com.example.editor.project"
+        );
+        assert_eq!(info.exported_types[0].extensions, ["edproj"]);
+        assert_eq!(
+            info.imported_types[0].identifier,
+            "net.daringfireball.markdown"
+        );
+    }
+
+    #[test]
+    fn parses_binary_plists() {
+        let xml = BundleInfo::from_file(&fixture("Editor.plist")).unwrap();
+        let binary = BundleInfo::from_file(&fixture("Editor.binary.plist")).unwrap();
+        assert_eq!(binary, xml);
+    }
+
+    #[test]
+    fn tolerates_loose_plists() {
+        let info = BundleInfo::from_file(&fixture("Agent.plist")).unwrap();
+
+        // Integer version, "1" and YES booleans, single-string lists
+        assert_eq!(info.version.as_deref(), Some("42"));
+        assert!(info.ui_element);
+        assert!(info.background_only);
+        assert_eq!(info.preferred_name(), Some("Agent"));
+
+        // The entry that isn't a dictionary is skipped, not fatal
+        assert_eq!(info.document_types.len(), 1);
+        let document = &info.document_types[0];
+        assert_eq!(document.extensions, ["log"]);
+        assert_eq!(document.rank, None);
+        assert_eq!(document.role, Some(BundleTypeRole::None));
+        assert!(info.url_types.is_empty());
+    }
+
+    #[test]
+    fn caches_until_the_plist_changes() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let app = tmp.path().join("Editor.app");
+        std::fs::create_dir_all(app.join("Contents")).unwrap();
+        std::fs::copy(fixture("Editor.plist"), info_plist_path(&app)).unwrap();
+
+        let first = BundleInfo::load(&app).unwrap();
+        assert!(Arc::ptr_eq(&first, &BundleInfo::load(&app).unwrap()));
+
+        std::fs::copy(fixture("Agent.plist"), info_plist_path(&app)).unwrap();
+        let modified = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(info_plist_path(&app))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(
+            BundleInfo::load(&app).unwrap().identifier.as_deref(),
+            Some("com.example.Agent")
+        );
+
+        assert!(matches!(
+            BundleInfo::load(tmp.path()),
+            Err(InfatError::InfoPlistNotFound { .. })
+        ));
+    }
+}
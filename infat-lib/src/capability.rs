@@ -0,0 +1,430 @@
+//! What an application declares it can open
+//!
+//! An app declares its document types in `CFBundleDocumentTypes` and its URL
+//! schemes in `CFBundleURLTypes`. Launch Services will still let any app be
+//! the default for anything, so these declarations are compared against the
+//! handlers that are actually set: an app bound to a type it doesn't declare
+//! usually can't open the file.
+//!
+//! Setting a handler checks the app's declarations first. By default an
+//! undeclared type or scheme is only a warning; an [`Enforcement`] passed to
+//! the setters makes it an error or turns the check off.
+
+use crate::bundle::BundleInfo;
+use crate::error::{InfatError, Result};
+use crate::pattern::normalize_extension;
+use crate::uti::UtiGraph;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use tracing::{debug, warn};
+
+/// What to do when an app is bound to something it doesn't declare
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Enforcement {
+    /// Log a warning and bind it anyway
+    #[default]
+    Warn,
+    /// Refuse to bind it
+    Strict,
+    /// Don't check
+    Off,
+}
+
+/// Something a handler can be the default for
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "lowercase")]
+pub enum Claim {
+    Type(String),
+    Extension(String),
+    Mime(String),
+    Scheme(String),
+}
+
+impl Claim {
+    /// The UTI a file claim stands for, if it has one
+    fn uti(&self, graph: &UtiGraph) -> Option<String> {
+        match self {
+            Self::Type(uti) => Some(uti.clone()),
+            Self::Extension(extension) => graph
+                .preferred_type_for_extension(extension)
+                .map(|declaration| declaration.identifier.clone()),
+            Self::Mime(mime_type) => graph
+                .preferred_type_for_mime_type(mime_type)
+                .map(|declaration| declaration.identifier.clone()),
+            Self::Scheme(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Claim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Type(uti) => write!(f, "{uti}"),
+            Self::Extension(extension) => write!(f, ".{extension}"),
+            Self::Mime(mime_type) => write!(f, "{mime_type}"),
+            Self::Scheme(scheme) => write!(f, "{scheme}://"),
+        }
+    }
+}
+
+/// How an app's declarations cover a claim
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "how", content = "via", rename_all = "lowercase")]
+pub enum Coverage {
+    /// Declared as is, or through one of the type's tags
+    Declared,
+    /// The app declares a type the claimed one conforms to
+    Ancestor(String),
+}
+
+/// The types, tags and schemes an app declares
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub types: BTreeSet<String>,
+    pub extensions: BTreeSet<String>,
+    pub mime_types: BTreeSet<String>,
+    pub schemes: BTreeSet<String>,
+}
+
+impl Capabilities {
+    pub fn of(info: &BundleInfo) -> Self {
+        let mut capabilities = Self::default();
+
+        for document_type in &info.document_types {
+            capabilities
+                .types
+                .extend(document_type.content_types.iter().cloned());
+            capabilities.extensions.extend(
+                document_type
+                    .extensions
+                    .iter()
+                    .map(|extension| normalize_extension(extension)),
+            );
+            capabilities.mime_types.extend(
+                document_type
+                    .mime_types
+                    .iter()
+                    .map(|mime_type| mime_type.to_lowercase()),
+            );
+        }
+        capabilities.schemes = info.url_schemes().map(str::to_lowercase).collect();
+
+        capabilities
+    }
+
+    /// Every declaration as a claim
+    pub fn claims(&self) -> Vec<Claim> {
+        let types = self.types.iter().cloned().map(Claim::Type);
+        let extensions = self
+            .extensions
+            .iter()
+            .filter(|extension| *extension != "*")
+            .cloned()
+            .map(Claim::Extension);
+        let mime_types = self.mime_types.iter().cloned().map(Claim::Mime);
+        let schemes = self.schemes.iter().cloned().map(Claim::Scheme);
+
+        types
+            .chain(extensions)
+            .chain(mime_types)
+            .chain(schemes)
+            .collect()
+    }
+
+    /// Whether, and how, the app declares it can handle a claim. A file claim
+    /// is covered by its own type, any type it conforms to, or one of its tags.
+    pub fn covers(&self, claim: &Claim, graph: &UtiGraph) -> Option<Coverage> {
+        let declared = match claim {
+            Claim::Scheme(scheme) => {
+                return self
+                    .schemes
+                    .contains(&scheme.to_lowercase())
+                    .then_some(Coverage::Declared)
+            }
+            Claim::Type(uti) => self.types.contains(uti),
+            Claim::Extension(extension) => {
+                let extension = normalize_extension(extension);
+                self.extensions.contains(&extension) || self.extensions.contains("*")
+            }
+            Claim::Mime(mime_type) => self.mime_types.contains(&mime_type.to_lowercase()),
+        };
+        if declared {
+            return Some(Coverage::Declared);
+        }
+
+        let uti = claim.uti(graph)?;
+        if self.types.contains(&uti) {
+            return Some(Coverage::Declared);
+        }
+
+        if let Some(declaration) = graph.get(&uti) {
+            let tags = declaration.tags();
+            let by_tag = tags
+                .extensions
+                .iter()
+                .any(|extension| self.extensions.contains(&normalize_extension(extension)))
+                || tags
+                    .mime_types
+                    .iter()
+                    .any(|mime_type| self.mime_types.contains(&mime_type.to_lowercase()));
+            if by_tag {
+                return Some(Coverage::Declared);
+            }
+        }
+
+        self.types
+            .iter()
+            .find(|declared| graph.conforms_to(&uti, declared))
+            .map(|ancestor| Coverage::Ancestor(ancestor.clone()))
+    }
+
+    /// Check that the app declares at least one of the claims a binding
+    /// stands for, e.g. an extension and the type it maps to. Depending on
+    /// `enforcement` a missing declaration is logged or returned as an error.
+    pub fn require(
+        &self,
+        app: &str,
+        claims: &[Claim],
+        graph: &UtiGraph,
+        enforcement: Enforcement,
+    ) -> Result<()> {
+        if enforcement == Enforcement::Off {
+            return Ok(());
+        }
+
+        if let Some(coverage) = claims.iter().find_map(|claim| self.covers(claim, graph)) {
+            debug!("{} declares {:?} ({:?})", app, claims, coverage);
+            return Ok(());
+        }
+
+        let Some(claim) = claims.first() else {
+            return Ok(());
+        };
+        let error = InfatError::UndeclaredCapability {
+            app: app.to_string(),
+            claim: claim.to_string(),
+        };
+        match enforcement {
+            Enforcement::Strict => Err(error),
+            _ => {
+                warn!("{}", error);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A Launch Services role an app holds for a claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HandlerRole {
+    All,
+    Viewer,
+    Editor,
+}
+
+impl std::fmt::Display for HandlerRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::All => "all",
+            Self::Viewer => "viewer",
+            Self::Editor => "editor",
+        })
+    }
+}
+
+/// An `LSHandlers` entry naming the app
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OwnedHandler {
+    pub claim: Claim,
+    pub roles: Vec<HandlerRole>,
+}
+
+/// An owned handler and whether the app declares it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Ownership {
+    #[serde(flatten)]
+    pub handler: OwnedHandler,
+    pub coverage: Option<Coverage>,
+}
+
+/// What an app is the default for, set against what it declares
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct OwnershipReport {
+    pub owned: Vec<Ownership>,
+    /// Declarations no handler entry gives the app
+    pub declared_unowned: Vec<Claim>,
+}
+
+impl OwnershipReport {
+    pub fn new(handlers: Vec<OwnedHandler>, capabilities: &Capabilities, graph: &UtiGraph) -> Self {
+        // Extensions and MIME types are compared by the type they stand for
+        let same = |a: &Claim, b: &Claim| {
+            a == b || matches!((a.uti(graph), b.uti(graph)), (Some(a), Some(b)) if a == b)
+        };
+
+        let declared_unowned = capabilities
+            .claims()
+            .into_iter()
+            .filter(|declared| {
+                !handlers
+                    .iter()
+                    .any(|handler| same(&handler.claim, declared))
+            })
+            .collect();
+
+        let mut owned: Vec<Ownership> = handlers
+            .into_iter()
+            .map(|handler| Ownership {
+                coverage: capabilities.covers(&handler.claim, graph),
+                handler,
+            })
+            .collect();
+        owned.sort_by(|a, b| a.handler.claim.cmp(&b.handler.claim));
+
+        Self {
+            owned,
+            declared_unowned,
+        }
+    }
+
+    /// Handlers the app holds without declaring the claim
+    pub fn owned_undeclared(&self) -> impl Iterator<Item = &Ownership> {
+        self.owned
+            .iter()
+            .filter(|ownership| ownership.coverage.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture() -> BundleInfo {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("Editor.plist");
+        BundleInfo::from_file(&path).unwrap()
+    }
+
+    fn editor() -> Capabilities {
+        Capabilities::of(&fixture())
+    }
+
+    /// The system types plus the ones the fixture declares
+    fn graph() -> UtiGraph {
+        let info = fixture();
+        let mut graph = UtiGraph::system().clone();
+        for declaration in info.exported_types.into_iter().chain(info.imported_types) {
+            graph.insert(declaration);
+        }
+        graph
+    }
+
+    #[test]
+    fn covers_declared_types_tags_and_schemes() {
+        let (editor, graph) = (editor(), graph());
+        let covers = |claim: Claim| editor.covers(&claim, &graph);
+
+        assert_eq!(
+            covers(Claim::Type("net.daringfireball.markdown".into())),
+            Some(Coverage::Declared)
+        );
+        assert_eq!(
+            covers(Claim::Extension(".MD".into())),
+            Some(Coverage::Declared)
+        );
+        assert_eq!(
+            covers(Claim::Type("com.example.editor.project".into())),
+            Some(Coverage::Declared)
+        );
+        assert_eq!(
+            covers(Claim::Scheme("X-Editor".into())),
+            Some(Coverage::Declared)
+        );
+        assert_eq!(covers(Claim::Scheme("mailto".into())), None);
+        assert_eq!(covers(Claim::Type("public.plain-text".into())), None);
+        assert_eq!(covers(Claim::Extension("pdf".into())), None);
+    }
+
+    #[test]
+    fn covers_types_conforming_to_a_declared_one() {
+        let graph = graph();
+        let text_editor = Capabilities {
+            types: BTreeSet::from(["public.text".to_string()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            text_editor.covers(&Claim::Extension("md".into()), &graph),
+            Some(Coverage::Ancestor("public.text".into()))
+        );
+        assert_eq!(
+            text_editor.covers(&Claim::Type("public.png".into()), &graph),
+            None
+        );
+    }
+
+    #[test]
+    fn requires_a_declaration_only_when_strict() {
+        let (editor, graph) = (editor(), graph());
+        let require =
+            |claims: &[Claim], enforcement| editor.require("Editor", claims, &graph, enforcement);
+        let pdf = [
+            Claim::Extension("pdf".into()),
+            Claim::Type("com.adobe.pdf".into()),
+        ];
+
+        assert!(require(&pdf, Enforcement::Warn).is_ok());
+        assert!(require(&pdf, Enforcement::Off).is_ok());
+        assert!(matches!(
+            require(&pdf, Enforcement::Strict),
+            Err(InfatError::UndeclaredCapability { claim, .. }) if claim == ".pdf"
+        ));
+
+        // The extension isn't declared, but the type it maps to is
+        let markdown = [
+            Claim::Extension("mdown".into()),
+            Claim::Type("net.daringfireball.markdown".into()),
+        ];
+        assert!(require(&markdown, Enforcement::Strict).is_ok());
+    }
+
+    #[test]
+    fn compares_owned_handlers_with_declarations() {
+        let (editor, graph) = (editor(), graph());
+        let handlers = vec![
+            OwnedHandler {
+                claim: Claim::Extension("md".into()),
+                roles: vec![HandlerRole::All],
+            },
+            OwnedHandler {
+                claim: Claim::Type("public.json".into()),
+                roles: vec![HandlerRole::Viewer, HandlerRole::Editor],
+            },
+            OwnedHandler {
+                claim: Claim::Scheme("editor".into()),
+                roles: vec![HandlerRole::All],
+            },
+        ];
+
+        let report = OwnershipReport::new(handlers, &editor, &graph);
+
+        let undeclared: Vec<_> = report
+            .owned_undeclared()
+            .map(|ownership| &ownership.handler.claim)
+            .collect();
+        assert_eq!(undeclared, [&Claim::Type("public.json".into())]);
+
+        // `.md` owns the markdown type through its extension
+        assert_eq!(
+            report.declared_unowned,
+            [
+                Claim::Extension("edproj".into()),
+                Claim::Scheme("x-editor".into())
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Dry-run validation of a configuration (`infat check`)
+//!
+//! Every entry is checked offline against the application index and the UTI
+//! graph: nothing is applied and Launch Services is never asked, so a typo is
+//! caught before it turns into a half-applied configuration.
+
+use crate::{
+    app_index::{AppIndex, CopyPolicy},
+    config::{self, Config, ConfigEntry, EntryKind},
+    error::InfatError,
+    pattern::{self, FilePattern},
+    suggest::Suggestions,
+    uti::{DynamicUti, SuperType, UtiGraph},
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::debug;
+
+/// How much an issue matters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Probably fine, e.g. a UTI only Launch Services knows about
+    Warning,
+    /// Applying the entry would fail
+    Error,
+}
+
+/// A problem found with one config entry
+#[derive(Debug, Clone, Serialize)]
+pub struct Issue {
+    pub kind: EntryKind,
+    pub key: String,
+    /// The user-defined supertype the entry was expanded from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supertype: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Suggestions::is_empty")]
+    pub suggestions: Suggestions,
+}
+
+impl Issue {
+    fn new(
+        entry: &ConfigEntry,
+        severity: Severity,
+        message: String,
+        suggestions: Suggestions,
+    ) -> Self {
+        Self {
+            kind: entry.kind,
+            key: entry.key.to_string(),
+            supertype: entry.supertype.map(str::to_string),
+            severity,
+            message,
+            suggestions,
+        }
+    }
+}
+
+/// The result of checking every entry in a configuration
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CheckReport {
+    /// Number of entries checked, after expanding user-defined supertypes
+    pub checked: usize,
+    pub issues: Vec<Issue>,
+    /// The types each `recursive` entry expands to, in the order they're applied
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub expanded: BTreeMap<String, Vec<String>>,
+}
+
+impl CheckReport {
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+}
+
+/// Check every entry of a configuration without applying it
+pub fn check_config(config: &Config, index: &AppIndex, graph: &UtiGraph) -> CheckReport {
+    let entries = config.entries_in(graph);
+    let mut report = CheckReport {
+        checked: entries.len(),
+        expanded: config::expansions(&entries),
+        ..Default::default()
+    };
+
+    for entry in &entries {
+        debug!("Checking {} → {}", entry, entry.binding);
+
+        if let Some(issue) = check_target(config, entry, index, graph) {
+            report.issues.push(issue);
+        }
+
+        if let Some(issue) = check_app(entry, index, config.settings.prefer_copy) {
+            report.issues.push(issue);
+        }
+    }
+
+    report
+}
+
+fn check_target(
+    config: &Config,
+    entry: &ConfigEntry,
+    index: &AppIndex,
+    graph: &UtiGraph,
+) -> Option<Issue> {
+    let key = &*entry.key;
+
+    match entry.kind {
+        EntryKind::Type if key.parse::<SuperType>().is_ok() => None,
+        EntryKind::Type if !key.contains('.') => {
+            let names = SuperType::catalogue()
+                .iter()
+                .map(|info| info.name)
+                .chain(config.supertypes.keys().map(String::as_str));
+            Some(Issue::new(
+                entry,
+                Severity::Error,
+                format!("'{key}' is not a supertype or UTI"),
+                Suggestions::closest(key, names),
+            ))
+        }
+        EntryKind::Type if !DynamicUti::is_dynamic(key) && !graph.contains(key) => {
+            Some(Issue::new(
+                entry,
+                Severity::Warning,
+                format!("UTI '{key}' is not declared by macOS or any installed app"),
+                Suggestions::types(key, graph),
+            ))
+        }
+        EntryKind::Mime if graph.types_for_mime_type(key).is_empty() => {
+            let mime_types = graph
+                .iter()
+                .flat_map(|declaration| declaration.mime_types.iter());
+            Some(Issue::new(
+                entry,
+                Severity::Warning,
+                format!("no known type has the MIME type '{key}'"),
+                Suggestions::closest(key, mime_types),
+            ))
+        }
+        EntryKind::Extension => check_extension(config, entry, key, graph),
+        EntryKind::File => match FilePattern::parse(key).extension() {
+            Ok(extension) => check_extension(config, entry, extension, graph),
+            Err(e) => Some(unrepresentable(entry, e)),
+        },
+        EntryKind::Scheme if !scheme_is_declared(index, key) => Some(Issue::new(
+            entry,
+            Severity::Warning,
+            format!("no installed app declares the '{key}' scheme"),
+            Suggestions::schemes(key, index),
+        )),
+        _ => None,
+    }
+}
+
+/// Compound extensions need something to claim the whole suffix
+fn check_extension(
+    config: &Config,
+    entry: &ConfigEntry,
+    extension: &str,
+    graph: &UtiGraph,
+) -> Option<Issue> {
+    let extension = pattern::normalize_extension(extension);
+    if pattern::compound_suffix(&extension).is_none() || config.declared(&extension).is_some() {
+        return None;
+    }
+
+    pattern::compound_uti(&extension, graph)
+        .err()
+        .map(|e| unrepresentable(entry, e))
+}
+
+fn unrepresentable(entry: &ConfigEntry, error: InfatError) -> Issue {
+    let message = match error {
+        InfatError::UnrepresentablePattern { reason, .. } => reason,
+        other => other.to_string(),
+    };
+    Issue::new(entry, Severity::Error, message, Suggestions::default())
+}
+
+fn check_app(entry: &ConfigEntry, index: &AppIndex, policy: CopyPolicy) -> Option<Issue> {
+    let app = &entry.binding.app;
+    let installed = if app.contains('/') {
+        Path::new(app).exists()
+    } else {
+        match index.find_by_name(app, policy) {
+            Ok(found) => found.is_some() || index.find_by_bundle_id(app, policy).is_some(),
+            Err(e) => {
+                return Some(Issue::new(
+                    entry,
+                    Severity::Error,
+                    e.to_string(),
+                    Suggestions::default(),
+                ));
+            }
+        }
+    };
+
+    (!installed).then(|| {
+        Issue::new(
+            entry,
+            Severity::Error,
+            format!("application '{app}' is not installed"),
+            Suggestions::apps(app, index),
+        )
+    })
+}
+
+fn scheme_is_declared(index: &AppIndex, scheme: &str) -> bool {
+    index.apps().iter().any(|app| {
+        app.url_schemes
+            .iter()
+            .any(|declared| declared.eq_ignore_ascii_case(scheme))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_misspellings_with_suggestions() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let contents = tmp.path().join("Zed.app").join("Contents");
+        std::fs::create_dir_all(&contents).unwrap();
+        std::fs::write(
+            contents.join("Info.plist"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>CFBundleIdentifier</key>
+    <string>dev.zed.Zed</string>
+    <key>CFBundleURLTypes</key>
+    <array>
+        <dict>
+            <key>CFBundleURLSchemes</key>
+            <array><string>zed</string></array>
+        </dict>
+    </array>
+</dict>
+</plist>
+"#,
+        )
+        .unwrap();
+
+        let index = AppIndex::build(&[tmp.path().to_path_buf()]);
+        let graph = UtiGraph::with_apps(index.apps());
+        let config: Config = toml::from_str(
+            r#"
+            [types]
+            plain_txt = "Zed"
+            sourcecode = "dev.zed.Zed"
+            "public.plain-txt" = "Zed"
+
+            [extensions]
+            md = "Zedd"
+            "tar.gz" = "Zed"
+
+            [files]
+            "*.d.ts" = "Zed"
+            ".env" = "Zed"
+
+            [declare]
+            "d.ts" = "com.microsoft.typescript"
+
+            [schemes]
+            zde = "Zed"
+            zed = "Zed"
+            "#,
+        )
+        .unwrap();
+
+        let report = check_config(&config, &index, &graph);
+        assert_eq!(report.checked, 9);
+
+        let issues: Vec<_> = report
+            .issues
+            .iter()
+            .map(|issue| {
+                (
+                    issue.key.as_str(),
+                    issue.severity,
+                    issue.suggestions.iter().next(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                ("plain_txt", Severity::Error, Some("plain-text")),
+                (
+                    "public.plain-txt",
+                    Severity::Warning,
+                    Some("public.plain-text")
+                ),
+                (".env", Severity::Error, None),
+                ("md", Severity::Error, Some("Zed")),
+                ("tar.gz", Severity::Error, None),
+                ("zde", Severity::Warning, Some("zed")),
+            ]
+        );
+        assert!(report.has_errors());
+        assert_eq!(report.warnings().count(), 2);
+    }
+}
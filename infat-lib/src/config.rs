@@ -1,23 +1,292 @@
-use crate::{
-    association,
-    error::{InfatError, Result},
-};
+use crate::app_index::{self, CopyPolicy};
+#[cfg(target_os = "macos")]
+use crate::association;
+#[cfg(target_os = "macos")]
+use crate::capability::Enforcement;
+use crate::discovery::SearchPath;
+use crate::error::{InfatError, Result};
+use crate::pattern;
+use crate::suggest::Suggestions;
+use crate::uti::SuperType;
+use crate::uti::UtiGraph;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tracing::{debug, info, warn};
+#[cfg(target_os = "macos")]
+use tracing::info;
+use tracing::{debug, warn};
 
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Config {
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub extensions: HashMap<String, String>,
+    pub extensions: HashMap<String, Binding>,
 
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub schemes: HashMap<String, String>,
+    pub schemes: HashMap<String, Binding>,
 
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
-    pub types: HashMap<String, String>,
+    pub types: HashMap<String, Binding>,
+
+    /// MIME types, e.g. `"application/pdf" = "Preview"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub mime: HashMap<String, Binding>,
+
+    /// Filename patterns, e.g. `"*.tar.gz" = "The Unarchiver"`; see [`pattern`]
+    /// for what macOS can represent
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub files: HashMap<String, Binding>,
+
+    /// User-defined supertypes that `[types]` entries can refer to by name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub supertypes: HashMap<String, SupertypeDefinition>,
+
+    /// Extension → UTI overrides, e.g. `ts = "com.microsoft.typescript"`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub declare: HashMap<String, String>,
+
+    #[serde(default, skip_serializing_if = "Settings::is_empty")]
+    pub settings: Settings,
+}
+
+/// How infat itself behaves
+///
+/// ```toml
+/// [settings]
+/// search_paths = ["/opt/homebrew/Caskroom", { path = "/Volumes/Tools/Applications", max_depth = 2 }]
+/// prefer_copy = "newest"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Settings {
+    /// Directories to search for applications, after the default ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_paths: Vec<SearchPath>,
+
+    /// Search only `search_paths`, not the default directories
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub replace_search_paths: bool,
+
+    /// Which copy of an app to use when it is installed more than once
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub prefer_copy: CopyPolicy,
+}
+
+fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+impl Settings {
+    pub fn is_empty(&self) -> bool {
+        self.search_paths.is_empty()
+            && !self.replace_search_paths
+            && self.prefer_copy == CopyPolicy::default()
+    }
+
+    /// Every directory to search for applications, in order, unless
+    /// `INFAT_APP_PATH` overrides them
+    pub fn search_paths(&self) -> Vec<SearchPath> {
+        let defaults = app_index::default_search_paths()
+            .into_iter()
+            .map(SearchPath::from)
+            .filter(|_| !self.replace_search_paths);
+
+        app_index::search_paths(defaults.chain(self.search_paths.iter().cloned()).collect())
+    }
+}
+
+/// A named group of UTIs and extensions, usable wherever a supertype is.
+///
+/// ```toml
+/// [supertypes]
+/// terraform = { utis = ["com.hashicorp.terraform"], extensions = ["tf", "tfvars"] }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupertypeDefinition {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub utis: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+}
+
+impl SupertypeDefinition {
+    pub fn is_empty(&self) -> bool {
+        self.utis.is_empty() && self.extensions.is_empty()
+    }
+}
+
+/// The app bound to a config entry.
+///
+/// Written either as a plain app name (`md = "TextEdit"`) or as a table with
+/// options (`md = { app = "TextEdit", enforce = false }`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BindingRepr", into = "BindingRepr")]
+pub struct Binding {
+    pub app: String,
+
+    /// Re-assert the association when another app takes it over (`infat watch`)
+    pub enforce: bool,
+
+    /// Also bind every type conforming to a `[types]` entry, unless a more
+    /// specific entry claims it
+    pub recursive: bool,
+}
+
+impl Binding {
+    fn has_default_options(&self) -> bool {
+        self.enforce && !self.recursive
+    }
+}
+
+impl From<String> for Binding {
+    fn from(app: String) -> Self {
+        Self {
+            app,
+            enforce: true,
+            recursive: false,
+        }
+    }
+}
+
+impl From<&str> for Binding {
+    fn from(app: &str) -> Self {
+        app.to_string().into()
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.app)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum BindingRepr {
+    App(String),
+    Table {
+        app: String,
+        #[serde(default = "enabled")]
+        enforce: bool,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        recursive: bool,
+    },
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl From<BindingRepr> for Binding {
+    fn from(repr: BindingRepr) -> Self {
+        match repr {
+            BindingRepr::App(app) => app.into(),
+            BindingRepr::Table {
+                app,
+                enforce,
+                recursive,
+            } => Self {
+                app,
+                enforce,
+                recursive,
+            },
+        }
+    }
+}
+
+impl From<Binding> for BindingRepr {
+    fn from(binding: Binding) -> Self {
+        // Keep the short form whenever there is nothing else to say
+        if binding.has_default_options() {
+            BindingRepr::App(binding.app)
+        } else {
+            BindingRepr::Table {
+                app: binding.app,
+                enforce: binding.enforce,
+                recursive: binding.recursive,
+            }
+        }
+    }
+}
+
+/// Which config table an entry belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    Type,
+    Mime,
+    Extension,
+    File,
+    Scheme,
+}
+
+impl std::fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Type => write!(f, "type"),
+            Self::Mime => write!(f, "MIME type"),
+            Self::Extension => write!(f, "extension"),
+            Self::File => write!(f, "file pattern"),
+            Self::Scheme => write!(f, "scheme"),
+        }
+    }
+}
+
+/// A single association from any of the config tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigEntry<'a> {
+    pub kind: EntryKind,
+    /// Owned for types found by expanding a `recursive` entry
+    pub key: Cow<'a, str>,
+    pub binding: &'a Binding,
+
+    /// The user-defined supertype or `recursive` entry this entry was expanded from
+    pub supertype: Option<&'a str>,
+}
+
+impl<'a> ConfigEntry<'a> {
+    fn new(kind: EntryKind, key: impl Into<Cow<'a, str>>, binding: &'a Binding) -> Self {
+        Self {
+            kind,
+            key: key.into(),
+            binding,
+            supertype: None,
+        }
+    }
+}
+
+/// The types each `recursive` entry expands to, in the order they're applied
+pub fn expansions(entries: &[ConfigEntry<'_>]) -> BTreeMap<String, Vec<String>> {
+    let mut expanded: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in entries {
+        if let (true, EntryKind::Type, Some(name)) =
+            (entry.binding.recursive, entry.kind, entry.supertype)
+        {
+            expanded
+                .entry(name.to_string())
+                .or_default()
+                .push(entry.key.to_string());
+        }
+    }
+    expanded
+}
+
+impl std::fmt::Display for ConfigEntry<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            EntryKind::Type => write!(f, "type {}", self.key)?,
+            EntryKind::Mime => write!(f, "MIME type {}", self.key)?,
+            EntryKind::Extension => write!(f, ".{}", self.key)?,
+            EntryKind::File => write!(f, "{}", self.key)?,
+            EntryKind::Scheme => write!(f, "{}", self.key)?,
+        }
+
+        if let Some(supertype) = self.supertype {
+            write!(f, " (via {supertype})")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,11 +294,17 @@ pub struct ConfigSummary {
     pub extensions_count: usize,
     pub schemes_count: usize,
     pub types_count: usize,
+    pub mime_count: usize,
+    pub files_count: usize,
 }
 
 impl ConfigSummary {
     pub fn total(&self) -> usize {
-        self.extensions_count + self.schemes_count + self.types_count
+        self.extensions_count
+            + self.schemes_count
+            + self.types_count
+            + self.mime_count
+            + self.files_count
     }
 }
 
@@ -61,7 +336,11 @@ impl Config {
 
     /// Check if the configuration is empty
     pub fn is_empty(&self) -> bool {
-        self.extensions.is_empty() && self.schemes.is_empty() && self.types.is_empty()
+        self.extensions.is_empty()
+            && self.schemes.is_empty()
+            && self.types.is_empty()
+            && self.mime.is_empty()
+            && self.files.is_empty()
     }
 
     /// Validate the configuration
@@ -72,27 +351,309 @@ impl Config {
             });
         }
 
+        for (name, definition) in &self.supertypes {
+            if definition.is_empty() {
+                return Err(InfatError::EmptySupertype { name: name.clone() });
+            }
+            if name.parse::<SuperType>().is_ok() {
+                warn!("Supertype '{}' shadows the built-in supertype", name);
+            }
+        }
+
         // Check for invalid keys in types
         for type_name in self.types.keys() {
             // Try parsing as SuperType or assume it's a UTI
-            if type_name.parse::<crate::uti::SuperType>().is_err() && !type_name.contains('.') {
-                warn!("Type '{}' may not be a valid UTI or supertype", type_name);
+            if self.supertype(type_name).is_none()
+                && type_name.parse::<SuperType>().is_err()
+                && !type_name.contains('.')
+            {
+                let names = SuperType::catalogue()
+                    .iter()
+                    .map(|info| info.name)
+                    .chain(self.supertypes.keys().map(String::as_str));
+                warn!(
+                    "Type '{}' may not be a valid UTI or supertype{}",
+                    type_name,
+                    Suggestions::closest(type_name, names)
+                );
+            }
+        }
+
+        for (extension, uti) in &self.declare {
+            if uti.parse::<SuperType>().is_err() && !uti.contains('.') {
+                let names = SuperType::catalogue().iter().map(|info| info.name);
+                warn!(
+                    "Declared type '{}' for .{} may not be a valid UTI or supertype{}",
+                    uti,
+                    extension,
+                    Suggestions::closest(uti, names)
+                );
+            }
+        }
+
+        for duplicate in duplicate_extensions(self.extensions.keys()) {
+            warn!(
+                "Extension '.{}' is listed more than once in [extensions]",
+                duplicate
+            );
+        }
+        for duplicate in duplicate_extensions(self.declare.keys()) {
+            warn!(
+                "Extension '.{}' is declared more than once in [declare], using the first key in sorted order",
+                duplicate
+            );
+        }
+
+        if self.settings.replace_search_paths && self.settings.search_paths.is_empty() {
+            warn!("[settings] replaces the search paths with none, so no apps will be found");
+        }
+        for search_path in &self.settings.search_paths {
+            if !search_path.path.exists() {
+                warn!(
+                    "Search path '{}' does not exist",
+                    search_path.path.display()
+                );
+            }
+        }
+
+        for mime_type in self.mime.keys() {
+            if !mime_type.contains('/') {
+                warn!("MIME type '{}' should look like 'type/subtype'", mime_type);
             }
         }
 
         Ok(())
     }
 
+    /// The UTI this configuration's `[declare]` table gives an extension.
+    ///
+    /// Values may be UTIs or supertype names. When several keys spell the same
+    /// extension (`.MD` and `md`), the first in sorted order wins.
+    pub fn declared(&self, extension: &str) -> Option<String> {
+        let extension = pattern::normalize_extension(extension);
+        self.declare
+            .iter()
+            .filter(|(declared, _)| pattern::normalize_extension(declared) == extension)
+            .min_by_key(|(declared, _)| declared.as_str())
+            .map(|(_, uti)| declared_type(uti))
+    }
+
+    /// Look up a user-defined supertype, spelled as loosely as built-in ones
+    pub fn supertype(&self, name: &str) -> Option<(&str, &SupertypeDefinition)> {
+        let normalize = |name: &str| name.trim().to_lowercase().replace('_', "-");
+        let wanted = normalize(name);
+
+        self.supertypes
+            .iter()
+            .find(|(defined, _)| normalize(defined) == wanted)
+            .map(|(defined, definition)| (defined.as_str(), definition))
+    }
+
+    /// The entries a `[types]` entry stands for: itself, or every UTI and
+    /// extension of the user-defined supertype it names.
+    ///
+    /// A `recursive` binding is expanded with every type infat knows of,
+    /// including the ones installed apps declare.
+    pub fn type_entries<'a>(&'a self, key: &'a str, binding: &'a Binding) -> Vec<ConfigEntry<'a>> {
+        if binding.recursive {
+            match UtiGraph::installed(&self.settings.search_paths()) {
+                Ok(graph) => return self.type_entries_in(key, binding, &graph),
+                Err(e) => warn!(
+                    "Could not load declared types, expanding {} with system types only: {}",
+                    key, e
+                ),
+            }
+        }
+        self.type_entries_in(key, binding, UtiGraph::system())
+    }
+
+    /// Like [`Config::type_entries`], expanding `recursive` bindings with `graph`
+    pub fn type_entries_in<'a>(
+        &'a self,
+        key: &'a str,
+        binding: &'a Binding,
+        graph: &UtiGraph,
+    ) -> Vec<ConfigEntry<'a>> {
+        let (name, mut entries) = match self.supertype(key) {
+            None => (key, vec![ConfigEntry::new(EntryKind::Type, key, binding)]),
+            Some((name, definition)) => {
+                let utis = definition
+                    .utis
+                    .iter()
+                    .map(|uti| ConfigEntry::new(EntryKind::Type, uti.as_str(), binding));
+                let extensions = definition.extensions.iter().map(|ext| {
+                    ConfigEntry::new(EntryKind::Extension, ext.trim_start_matches('.'), binding)
+                });
+                let entries = utis
+                    .chain(extensions)
+                    .map(|entry| ConfigEntry {
+                        supertype: Some(name),
+                        ..entry
+                    })
+                    .collect();
+                (name, entries)
+            }
+        };
+
+        if binding.recursive {
+            entries.extend(self.descendant_entries(key, name, binding, graph));
+        }
+        entries
+    }
+
+    /// Every type conforming to a `recursive` entry's UTIs, except the ones a
+    /// more specific `[types]` entry claims
+    fn descendant_entries<'a>(
+        &'a self,
+        key: &str,
+        name: &'a str,
+        binding: &'a Binding,
+        graph: &UtiGraph,
+    ) -> Vec<ConfigEntry<'a>> {
+        let roots = self.type_utis(key);
+        let claimed: Vec<String> = self
+            .types
+            .keys()
+            .filter(|other| other.as_str() != key)
+            .flat_map(|other| self.type_utis(other))
+            .collect();
+
+        let mut seen: HashSet<String> = roots.iter().map(|uti| uti.to_lowercase()).collect();
+        let mut entries = Vec::new();
+
+        for root in &roots {
+            for descendant in graph.descendants(root) {
+                if !seen.insert(descendant.to_lowercase())
+                    || SCHEME_ROUTED_TYPES.contains(&descendant)
+                {
+                    continue;
+                }
+
+                let claimed_by = claimed.iter().find(|uti| {
+                    !uti.eq_ignore_ascii_case(root)
+                        && graph.conforms_to(descendant, uti)
+                        && graph.conforms_to(uti, root)
+                });
+                if let Some(uti) = claimed_by {
+                    debug!(
+                        "Not expanding {} to {}: {} is more specific",
+                        name, descendant, uti
+                    );
+                    continue;
+                }
+
+                entries.push(ConfigEntry {
+                    supertype: Some(name),
+                    ..ConfigEntry::new(EntryKind::Type, descendant.to_string(), binding)
+                });
+            }
+        }
+
+        entries
+    }
+
+    /// The UTIs a `[types]` key stands for
+    fn type_utis(&self, key: &str) -> Vec<String> {
+        if let Some((_, definition)) = self.supertype(key) {
+            return definition.utis.clone();
+        }
+
+        match key.parse::<SuperType>() {
+            Ok(supertype) => vec![supertype.uti_string().to_string()],
+            Err(_) if key.contains('.') => vec![key.to_string()],
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// All entries in the order they are applied: types, MIME types, file
+    /// patterns, extensions, then schemes.
+    ///
+    /// `[types]` entries naming a user-defined supertype are expanded in place, so
+    /// explicit `[extensions]` entries still override them, as they do `*.ext`
+    /// patterns in `[files]`.
+    pub fn entries(&self) -> Vec<ConfigEntry<'_>> {
+        self.collect_entries(|key, binding| self.type_entries(key, binding))
+    }
+
+    /// Like [`Config::entries`], expanding `recursive` bindings with `graph`
+    pub fn entries_in<'a>(&'a self, graph: &UtiGraph) -> Vec<ConfigEntry<'a>> {
+        self.collect_entries(|key, binding| self.type_entries_in(key, binding, graph))
+    }
+
+    fn collect_entries<'a>(
+        &'a self,
+        type_entries: impl Fn(&'a str, &'a Binding) -> Vec<ConfigEntry<'a>>,
+    ) -> Vec<ConfigEntry<'a>> {
+        fn table(kind: EntryKind, map: &HashMap<String, Binding>) -> Vec<ConfigEntry<'_>> {
+            let mut entries: Vec<_> = map
+                .iter()
+                .map(|(key, binding)| ConfigEntry::new(kind, key.as_str(), binding))
+                .collect();
+            entries.sort_by(|a, b| a.key.cmp(&b.key));
+            entries
+        }
+
+        let mut types: Vec<_> = self.types.iter().collect();
+        types.sort_by_key(|(key, _)| key.as_str());
+        let mut entries: Vec<_> = types
+            .into_iter()
+            .flat_map(|(key, binding)| type_entries(key, binding))
+            .collect();
+        entries.extend(table(EntryKind::Mime, &self.mime));
+        entries.extend(table(EntryKind::File, &self.files));
+        entries.extend(
+            table(EntryKind::Extension, &self.extensions)
+                .into_iter()
+                .map(|entry| match entry.key {
+                    Cow::Borrowed(key) => ConfigEntry {
+                        key: Cow::Borrowed(key.trim_start_matches('.')),
+                        ..entry
+                    },
+                    Cow::Owned(_) => entry,
+                }),
+        );
+        entries.extend(table(EntryKind::Scheme, &self.schemes));
+        entries
+    }
+
     /// Get summary statistics
     pub fn summary(&self) -> ConfigSummary {
         ConfigSummary {
             extensions_count: self.extensions.len(),
             schemes_count: self.schemes.len(),
             types_count: self.types.len(),
+            mime_count: self.mime.len(),
+            files_count: self.files.len(),
         }
     }
 }
 
+/// Types whose handler is really a URL scheme's; expanding into them would
+/// take over the browser
+const SCHEME_ROUTED_TYPES: &[&str] = &["public.html", "com.apple.default-app.web-browser"];
+
+/// Extensions that more than one key of a table normalizes to, each once
+fn duplicate_extensions<'a>(keys: impl Iterator<Item = &'a String>) -> Vec<String> {
+    let mut extensions: Vec<_> = keys
+        .map(|extension| pattern::normalize_extension(extension))
+        .collect();
+    extensions.sort();
+
+    let mut duplicates: Vec<String> = extensions
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0].clone())
+        .collect();
+    duplicates.dedup();
+    duplicates
+}
+
+/// A `[declare]` value as a UTI; supertype names stand for their UTI
+fn declared_type(uti: &str) -> String {
+    uti.parse::<SuperType>()
+        .map_or_else(|_| uti.to_string(), |st| st.uti_string().to_string())
+}
+
 /// Get XDG-compliant configuration file paths in order of preference
 pub fn get_config_paths() -> Result<Vec<std::path::PathBuf>> {
     let mut paths = Vec::new();
@@ -125,8 +686,32 @@ pub fn find_config_file() -> Result<Option<std::path::PathBuf>> {
     Ok(get_config_paths()?.into_iter().find(|path| path.exists()))
 }
 
+/// Apply a single config entry
+#[cfg(target_os = "macos")]
+pub fn apply_entry(entry: &ConfigEntry, config: &Config, enforcement: Enforcement) -> Result<()> {
+    let app_name = &entry.binding.app;
+    match entry.kind {
+        EntryKind::Type => {
+            association::set_default_app_for_type(&entry.key, app_name, config, enforcement)
+        }
+        EntryKind::Mime => {
+            association::set_default_app_for_mime_type(&entry.key, app_name, config, enforcement)
+        }
+        EntryKind::Extension => {
+            association::set_default_app_for_extension(&entry.key, app_name, config, enforcement)
+        }
+        EntryKind::File => {
+            association::set_default_app_for_file(&entry.key, app_name, config, enforcement)
+        }
+        EntryKind::Scheme => {
+            association::set_default_app_for_url_scheme(&entry.key, app_name, config, enforcement)
+        }
+    }
+}
+
 /// Apply configuration settings
-pub  fn apply_config(config: &Config, robust: bool) -> Result<()> {
+#[cfg(target_os = "macos")]
+pub fn apply_config(config: &Config, robust: bool, enforcement: Enforcement) -> Result<()> {
     info!("Applying configuration settings");
 
     config.validate()?;
@@ -137,87 +722,47 @@ pub  fn apply_config(config: &Config, robust: bool) -> Result<()> {
         summary.total()
     );
 
-    let mut errors = Vec::new();
-    let mut success_count = 0;
-
-    // Apply types first
-    if !config.types.is_empty() {
+    let entries = config.entries();
+    for (name, utis) in expansions(&entries) {
         info!(
-            "Processing [types] associations ({} entries)...",
-            config.types.len()
+            "{} expands to {} types: {}",
+            name,
+            utis.len(),
+            utis.join(", ")
         );
-        for (type_name, app_name) in &config.types {
-            match association::set_default_app_for_type(type_name, app_name) {
-                Ok(_) => {
-                    info!("✓ Set type {} → {}", type_name, app_name);
-                    success_count += 1;
-                }
-                Err(e) => {
-                    let msg = format!("Failed to set type {type_name} → {app_name}: {e}");
-                    if robust {
-                        warn!("{}", msg);
-                        errors.push(msg);
-                    } else {
-                        return Err(e);
-                    }
-                }
-            }
-        }
     }
 
-    // Apply extensions
-    if !config.extensions.is_empty() {
-        info!(
-            "Processing [extensions] associations ({} entries)...",
-            config.extensions.len()
-        );
-        for (ext, app_name) in &config.extensions {
-            match association::set_default_app_for_extension(ext, app_name) {
-                Ok(_) => {
-                    info!("✓ Set .{} → {}", ext, app_name);
-                    success_count += 1;
-                }
-                Err(e) => {
-                    let msg = format!("Failed to set .{ext} → {app_name}: {e}");
-                    if robust {
-                        warn!("{}", msg);
-                        errors.push(msg);
-                    } else {
-                        return Err(e);
-                    }
-                }
-            }
-        }
-    }
+    let mut errors = Vec::new();
+    let mut success_count = 0;
+    let mut skipped_count = 0;
 
-    // Apply schemes
-    if !config.schemes.is_empty() {
-        info!(
-            "Processing [schemes] associations ({} entries)...",
-            config.schemes.len()
-        );
-        for (scheme, app_name) in &config.schemes {
-            match association::set_default_app_for_url_scheme(scheme, app_name) {
-                Ok(_) => {
-                    info!("✓ Set {} → {}", scheme, app_name);
-                    success_count += 1;
-                }
-                Err(e) => {
-                    let msg = format!("Failed to set {scheme} → {app_name}: {e}");
-                    if robust {
-                        warn!("{}", msg);
-                        errors.push(msg);
-                    } else {
-                        return Err(e);
-                    }
+    for entry in entries {
+        match apply_entry(&entry, config, enforcement) {
+            Ok(_) => {
+                info!("✓ Set {} → {}", entry, entry.binding);
+                success_count += 1;
+            }
+            // Never applying it is the only correct outcome, so don't fail the rest
+            Err(e @ InfatError::UnrepresentablePattern { .. }) => {
+                warn!("Skipped {entry} → {}: {e}", entry.binding);
+                skipped_count += 1;
+            }
+            Err(e) => {
+                let msg = format!("Failed to set {entry} → {}: {e}", entry.binding);
+                if robust {
+                    warn!("{}", msg);
+                    errors.push(msg);
+                } else {
+                    return Err(e);
                 }
             }
         }
     }
 
     info!(
-        "Configuration applied: {} successful, {} errors",
+        "Configuration applied: {} successful, {} skipped, {} errors",
         success_count,
+        skipped_count,
         errors.len()
     );
 
@@ -233,3 +778,216 @@ pub  fn apply_config(config: &Config, robust: bool) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_user_supertypes() {
+        let config: Config = toml::from_str(
+            r#"
+            [supertypes]
+            terraform = { utis = ["com.hashicorp.terraform"], extensions = [".tf", "tfvars"] }
+
+            [types]
+            Terraform = "Zed"
+            plain-text = "TextEdit"
+
+            [extensions]
+            tfvars = "Nova"
+            "#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let entries = config.entries();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.kind,
+                    &*entry.key,
+                    entry.binding.app.as_str(),
+                    entry.supertype,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            entries,
+            [
+                (
+                    EntryKind::Type,
+                    "com.hashicorp.terraform",
+                    "Zed",
+                    Some("terraform")
+                ),
+                (EntryKind::Extension, "tf", "Zed", Some("terraform")),
+                (EntryKind::Extension, "tfvars", "Zed", Some("terraform")),
+                (EntryKind::Type, "plain-text", "TextEdit", None),
+                (EntryKind::Extension, "tfvars", "Nova", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn declares_extension_overrides() {
+        let config: Config = toml::from_str(
+            r#"
+            [declare]
+            ts = "com.microsoft.typescript"
+            ".MJS" = "javascript"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.declared(".TS").as_deref(),
+            Some("com.microsoft.typescript")
+        );
+        assert_eq!(
+            config.declared("mjs").as_deref(),
+            Some("com.netscape.javascript-source")
+        );
+        assert_eq!(config.declared("rs"), None);
+        assert_eq!(
+            config.declared("*.mjs").as_deref(),
+            Some("com.netscape.javascript-source")
+        );
+    }
+
+    #[test]
+    fn picks_colliding_declarations_in_sorted_order() {
+        let config: Config = toml::from_str(
+            r#"
+            [declare]
+            md = "net.daringfireball.markdown"
+            ".MD" = "public.plain-text"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.declared("md").as_deref(), Some("public.plain-text"));
+        assert_eq!(duplicate_extensions(config.declare.keys()), ["md"]);
+    }
+
+    #[test]
+    fn expands_recursive_types_to_descendants() {
+        let config: Config = toml::from_str(
+            r#"
+            [types]
+            sourcecode = { app = "Zed", recursive = true }
+            "public.script" = "BBEdit"
+            "#,
+        )
+        .unwrap();
+
+        let entries = config.entries_in(UtiGraph::system());
+        let zed: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.binding.app == "Zed")
+            .map(|entry| &*entry.key)
+            .collect();
+
+        assert_eq!(zed[0], "sourcecode");
+        assert!(zed.contains(&"public.swift-source"));
+        assert!(zed.contains(&"com.microsoft.typescript"));
+        // public.script and everything under it belong to the more specific entry
+        assert!(!zed.contains(&"public.script"));
+        assert!(!zed.contains(&"public.python-script"));
+        assert!(entries
+            .iter()
+            .filter(|entry| entry.binding.app == "Zed" && entry.key != "sourcecode")
+            .all(|entry| entry.supertype == Some("sourcecode")));
+        assert_eq!(
+            expansions(&entries).into_iter().collect::<Vec<_>>(),
+            [(
+                "sourcecode".to_string(),
+                zed[1..].iter().map(|uti| uti.to_string()).collect()
+            )]
+        );
+
+        let serialized = toml::to_string(&config).unwrap();
+        assert!(serialized.contains("recursive = true"));
+        assert!(serialized.contains(r#""public.script" = "BBEdit""#));
+    }
+
+    #[test]
+    fn adds_or_replaces_search_paths() {
+        let config: Config = toml::from_str(
+            r#"
+            [settings]
+            search_paths = [
+                "/opt/homebrew/Caskroom",
+                { path = "/Volumes/Tools/Applications", max_depth = 2, exclude = ["Parallels Desktop.app"] },
+            ]
+            prefer_copy = "newest"
+            "#,
+        )
+        .unwrap();
+
+        let paths = config.settings.search_paths();
+        let defaults = app_index::default_search_paths().len();
+        assert_eq!(paths.len(), defaults + 2);
+        assert_eq!(paths[0].path, PathBuf::from("/Applications"));
+        assert_eq!(paths[defaults + 1].max_depth, Some(2));
+        assert_eq!(paths[defaults + 1].exclude, ["Parallels Desktop.app"]);
+        assert_eq!(config.settings.prefer_copy, CopyPolicy::Newest);
+
+        let replaced = Settings {
+            replace_search_paths: true,
+            ..config.settings.clone()
+        };
+        assert_eq!(replaced.search_paths(), config.settings.search_paths[..]);
+
+        // The short form survives a round trip
+        let serialized = toml::to_string(&config).unwrap();
+        assert!(serialized.contains(r#""/opt/homebrew/Caskroom""#));
+        assert!(serialized.contains("max_depth = 2"));
+        assert!(serialized.contains(r#"prefer_copy = "newest""#));
+        assert!(!toml::to_string(&Config::default())
+            .unwrap()
+            .contains("prefer_copy"));
+    }
+
+    #[test]
+    fn applies_file_patterns_before_extensions() {
+        let config: Config = toml::from_str(
+            r#"
+            [files]
+            "*.md" = "Typora"
+            Makefile = "Zed"
+
+            [extensions]
+            ".md" = "Zed"
+            "#,
+        )
+        .unwrap();
+
+        let entries = config.entries();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.kind, &*entry.key, entry.to_string()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (EntryKind::File, "*.md", "*.md".to_string()),
+                (EntryKind::File, "Makefile", "Makefile".to_string()),
+                (EntryKind::Extension, "md", ".md".to_string()),
+            ]
+        );
+        assert_eq!(config.summary().total(), 3);
+    }
+
+    #[test]
+    fn rejects_empty_supertypes() {
+        let config: Config =
+            toml::from_str("[supertypes]\nnothing = {}\n\n[types]\nnothing = \"Zed\"").unwrap();
+        assert!(matches!(
+            config.validate(),
+            Err(InfatError::EmptySupertype { .. })
+        ));
+    }
+}
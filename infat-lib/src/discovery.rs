@@ -0,0 +1,409 @@
+//! Parallel discovery of application bundles on disk
+//!
+//! Directories are read concurrently on a rayon pool. Every directory is tracked
+//! by its canonical path, so symlinks that point back up the tree (or two roots
+//! that overlap) are only ever walked once.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// How deep below a search root the walker will descend by default
+pub const DEFAULT_MAX_DEPTH: usize = 8;
+
+/// Options controlling how the search paths are walked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkOptions {
+    /// Maximum number of directories to descend below each root
+    pub max_depth: usize,
+
+    /// Entries to skip. A bare name (`node_modules`, `Foo.app`) matches that file name
+    /// anywhere; a pattern containing `/` matches a path relative to its search root,
+    /// or an absolute path when it starts with `/`.
+    pub ignore: Vec<String>,
+
+    /// Worker threads to use, or `None` to use one per core
+    #[serde(skip)]
+    pub threads: Option<usize>,
+}
+
+impl Default for WalkOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            ignore: Vec::new(),
+            threads: None,
+        }
+    }
+}
+
+impl WalkOptions {
+    /// Run `op` on a thread pool sized according to these options
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads.unwrap_or(0))
+            .build()
+        {
+            Ok(pool) => pool.install(op),
+            Err(e) => {
+                debug!("Could not build thread pool, using the global pool: {}", e);
+                op()
+            }
+        }
+    }
+
+    fn is_ignored(&self, root: &SearchPath, path: &Path) -> bool {
+        self.ignore.iter().chain(&root.exclude).any(|pattern| {
+            if pattern.starts_with('/') {
+                path.starts_with(pattern)
+            } else if pattern.contains('/') {
+                path.strip_prefix(&root.path)
+                    .is_ok_and(|relative| relative.starts_with(pattern))
+            } else {
+                path.file_name()
+                    .is_some_and(|name| name == pattern.as_str())
+            }
+        })
+    }
+
+    fn max_depth(&self, root: &SearchPath) -> usize {
+        root.max_depth.unwrap_or(self.max_depth)
+    }
+}
+
+/// A directory to search for applications, with limits of its own.
+///
+/// Written either as a plain path or as a table:
+///
+/// ```toml
+/// search_paths = [
+///     "/opt/homebrew/Caskroom",
+///     { path = "~/Applications", max_depth = 2, exclude = ["Parallels Desktop.app"] },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SearchPathRepr", into = "SearchPathRepr")]
+pub struct SearchPath {
+    pub path: PathBuf,
+
+    /// Overrides [`WalkOptions::max_depth`] below this path
+    pub max_depth: Option<usize>,
+
+    /// Entries to skip below this path, in addition to [`WalkOptions::ignore`]
+    /// and with the same syntax
+    pub exclude: Vec<String>,
+}
+
+impl SearchPath {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_depth: None,
+            exclude: Vec::new(),
+        }
+    }
+}
+
+impl From<PathBuf> for SearchPath {
+    fn from(path: PathBuf) -> Self {
+        Self::new(path)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SearchPathRepr {
+    Path(String),
+    Table {
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_depth: Option<usize>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        exclude: Vec<String>,
+    },
+}
+
+impl From<SearchPathRepr> for SearchPath {
+    fn from(repr: SearchPathRepr) -> Self {
+        match repr {
+            SearchPathRepr::Path(path) => Self::new(expand_home(&path)),
+            SearchPathRepr::Table {
+                path,
+                max_depth,
+                exclude,
+            } => Self {
+                path: expand_home(&path),
+                max_depth,
+                exclude,
+            },
+        }
+    }
+}
+
+impl From<SearchPath> for SearchPathRepr {
+    fn from(search_path: SearchPath) -> Self {
+        let path = search_path.path.to_string_lossy().into_owned();
+
+        // Keep the short form whenever there is nothing else to say
+        if search_path.max_depth.is_none() && search_path.exclude.is_empty() {
+            SearchPathRepr::Path(path)
+        } else {
+            SearchPathRepr::Table {
+                path,
+                max_depth: search_path.max_depth,
+                exclude: search_path.exclude,
+            }
+        }
+    }
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Everything found by a walk
+#[derive(Debug, Default)]
+pub struct Walk {
+    /// Application bundles, grouped by search root in the order the roots were given
+    pub apps: Vec<PathBuf>,
+
+    /// Every directory the walker visited or tried to visit, including missing roots
+    pub directories: Vec<PathBuf>,
+}
+
+#[derive(Default)]
+struct WalkState {
+    visited: Mutex<HashSet<PathBuf>>,
+    apps: Mutex<Vec<(usize, PathBuf)>>,
+    directories: Mutex<Vec<PathBuf>>,
+}
+
+impl WalkState {
+    /// Record a directory, returning false if it has been walked before
+    fn enter(&self, canonical: &Path) -> bool {
+        self.visited
+            .lock()
+            .map(|mut visited| visited.insert(canonical.to_path_buf()))
+            .unwrap_or(false)
+    }
+}
+
+/// Walk the search roots in parallel, collecting every `.app` bundle
+pub fn walk(roots: &[SearchPath], options: &WalkOptions) -> Walk {
+    options.install(|| walk_in_current_pool(roots, options))
+}
+
+/// Walk on whichever rayon pool the caller is running in
+pub(crate) fn walk_in_current_pool(roots: &[SearchPath], options: &WalkOptions) -> Walk {
+    debug!(
+        "Walking {} search paths (max depth {})",
+        roots.len(),
+        options.max_depth
+    );
+
+    let state = WalkState::default();
+    let canonical_roots: Vec<SearchPath> = roots
+        .iter()
+        .map(|root| SearchPath {
+            path: fs::canonicalize(&root.path).unwrap_or_else(|_| root.path.clone()),
+            ..root.clone()
+        })
+        .collect();
+
+    rayon::scope(|scope| {
+        for (root_index, root) in canonical_roots.iter().enumerate() {
+            let state = &state;
+            scope.spawn(move |scope| {
+                visit(
+                    scope,
+                    root_index,
+                    root,
+                    root.path.clone(),
+                    0,
+                    state,
+                    options,
+                );
+            });
+        }
+    });
+
+    let mut apps = state.apps.into_inner().unwrap_or_default();
+    apps.sort();
+
+    let mut directories = state.directories.into_inner().unwrap_or_default();
+    directories.sort();
+
+    debug!(
+        "Walk found {} apps in {} directories",
+        apps.len(),
+        directories.len()
+    );
+
+    Walk {
+        apps: apps.into_iter().map(|(_, path)| path).collect(),
+        directories,
+    }
+}
+
+fn visit<'s>(
+    scope: &rayon::Scope<'s>,
+    root_index: usize,
+    root: &'s SearchPath,
+    dir: PathBuf,
+    depth: usize,
+    state: &'s WalkState,
+    options: &'s WalkOptions,
+) {
+    if !state.enter(&dir) {
+        debug!("Skipping already visited directory: {}", dir.display());
+        return;
+    }
+
+    if let Ok(mut directories) = state.directories.lock() {
+        directories.push(dir.clone());
+    }
+
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            debug!("Could not read directory {}: {}", dir.display(), e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let entry_path = entry.path();
+
+        if options.is_ignored(root, &entry_path) {
+            debug!("Ignoring {}", entry_path.display());
+            continue;
+        }
+
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+
+        // Follow symlinks to get the actual target
+        let resolved_path = if file_type.is_symlink() {
+            match fs::canonicalize(&entry_path) {
+                Ok(canonical) => canonical,
+                Err(_) => continue, // Skip broken symlinks
+            }
+        } else {
+            entry_path.clone()
+        };
+
+        if resolved_path.extension().is_some_and(|ext| ext == "app") {
+            if let Ok(mut apps) = state.apps.lock() {
+                apps.push((root_index, entry_path));
+            }
+        } else if depth < options.max_depth(root)
+            && (file_type.is_dir() || (file_type.is_symlink() && resolved_path.is_dir()))
+        {
+            scope.spawn(move |scope| {
+                visit(
+                    scope,
+                    root_index,
+                    root,
+                    resolved_path,
+                    depth + 1,
+                    state,
+                    options,
+                )
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temporary tree of the given directories, with its canonical root
+    fn tree(dirs: &[&str]) -> (tempfile::TempDir, PathBuf) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let root = fs::canonicalize(tmp.path()).unwrap();
+        for dir in dirs {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        (tmp, root)
+    }
+
+    fn app_names(walk: &Walk, root: &Path) -> Vec<String> {
+        let mut names: Vec<String> = walk
+            .apps
+            .iter()
+            .map(|app| app.strip_prefix(root).unwrap().display().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walks_symlink_loops_once() {
+        let (_tmp, root) = tree(&["Vendor/Tool.app"]);
+        std::os::unix::fs::symlink(&root, root.join("Vendor").join("Loop")).unwrap();
+        std::os::unix::fs::symlink(root.join("Vendor"), root.join("Alias")).unwrap();
+
+        let walk = walk(&[SearchPath::new(&root)], &WalkOptions::default());
+
+        assert_eq!(app_names(&walk, &root), ["Vendor/Tool.app"]);
+        let vendor = walk
+            .directories
+            .iter()
+            .filter(|dir| **dir == root.join("Vendor"))
+            .count();
+        assert_eq!(vendor, 1);
+    }
+
+    #[test]
+    fn stops_at_the_maximum_depth() {
+        let (_tmp, root) = tree(&["Top.app", "One/Nested.app", "One/Two/Deep.app"]);
+
+        let options = WalkOptions {
+            max_depth: 1,
+            ..WalkOptions::default()
+        };
+        let walk = walk(&[SearchPath::new(&root)], &options);
+        assert_eq!(app_names(&walk, &root), ["One/Nested.app", "Top.app"]);
+
+        let unlimited = super::walk(&[SearchPath::new(&root)], &WalkOptions::default());
+        assert_eq!(unlimited.apps.len(), 3);
+    }
+
+    #[test]
+    fn ignores_absolute_relative_and_bare_patterns() {
+        let (_tmp, root) = tree(&[
+            "Keep.app",
+            "Old/Legacy.app",
+            "Vendor/Cache/Cached.app",
+            "Vendor/Tool.app",
+            "Other/Skipped.app",
+            "Other/node_modules/Electron.app",
+            "node_modules/Bundled.app",
+        ]);
+
+        let options = WalkOptions {
+            ignore: vec![
+                root.join("Old").display().to_string(),
+                "Vendor/Cache".to_string(),
+                "node_modules".to_string(),
+                "Skipped.app".to_string(),
+            ],
+            ..WalkOptions::default()
+        };
+        let walk = walk(&[SearchPath::new(&root)], &options);
+
+        assert_eq!(app_names(&walk, &root), ["Keep.app", "Vendor/Tool.app"]);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::app_index::Candidates;
+use crate::suggest::Suggestions;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -8,6 +10,9 @@ pub enum InfatError {
     #[error("Could not derive UTI for extension '.{extension}'")]
     CouldNotDeriveUTI { extension: String },
 
+    #[error("Could not derive UTI for MIME type '{mime_type}'")]
+    CouldNotDeriveUTIForMimeType { mime_type: String },
+
     #[error("System service '{bundle}' cannot be used as default application")]
     SystemService { bundle: String },
 
@@ -20,8 +25,23 @@ pub enum InfatError {
     #[error("Info.plist not found in application bundle: {app_path}")]
     InfoPlistNotFound { app_path: PathBuf },
 
-    #[error("Unsupported or invalid supertype: {name}")]
-    UnsupportedSupertype { name: String },
+    #[error("Unsupported or invalid supertype: {name}{suggestions}")]
+    UnsupportedSupertype {
+        name: String,
+        suggestions: Suggestions,
+    },
+
+    #[error("Unknown supertype category: {name}{suggestions}")]
+    UnknownCategory {
+        name: String,
+        suggestions: Suggestions,
+    },
+
+    #[error("Supertype '{name}' defines no UTIs or extensions")]
+    EmptySupertype { name: String },
+
+    #[error("'{pattern}' can't be represented by macOS: {reason}")]
+    UnrepresentablePattern { pattern: String, reason: String },
 
     #[error("Cannot set URL scheme for application '{app_name}'")]
     CannotSetURL { app_name: String },
@@ -48,13 +68,27 @@ pub enum InfatError {
     #[error("Could not expand path: {path}")]
     PathExpansionError { path: PathBuf },
 
-    #[error("Application not found: {name}")]
-    ApplicationNotFound { name: String },
+    #[error("Application not found: {name}{suggestions}")]
+    ApplicationNotFound {
+        name: String,
+        suggestions: Suggestions,
+    },
+
+    #[error(
+        "'{name}' matches several applications, use a bundle ID or path to pick one:{candidates}"
+    )]
+    AmbiguousApplication {
+        name: String,
+        candidates: Candidates,
+    },
+
+    #[error("{app} doesn't declare {claim} in its Info.plist, so it may not be able to open it")]
+    UndeclaredCapability { app: String, claim: String },
 
     #[error("Could not get bundle identifier from path: {path}")]
     BundleIdNotFound { path: PathBuf },
 
-    #[error("Error reading Info.plist at '{path}'")]
+    #[error("Error reading property list at '{path}'")]
     PlistReadError {
         path: PathBuf,
         #[source]
@@ -126,7 +160,10 @@ where
     E: std::error::Error + Send + Sync + 'static,
 {
     fn app_not_found(self, name: impl Into<String>) -> Result<T> {
-        self.map_err(|_| InfatError::ApplicationNotFound { name: name.into() })
+        self.map_err(|_| InfatError::ApplicationNotFound {
+            name: name.into(),
+            suggestions: Suggestions::default(),
+        })
     }
 
     fn config_load_error(self, path: impl Into<PathBuf>) -> Result<T> {
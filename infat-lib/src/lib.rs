@@ -4,11 +4,24 @@
 //! This library provides functionality to inspect and modify default applications
 //! for file types and URL schemes on macOS using Launch Services.
 
+pub mod agent;
+#[cfg(target_os = "macos")]
 pub mod app;
+pub mod app_index;
+#[cfg(target_os = "macos")]
 pub mod association;
+pub mod bundle;
+pub mod capability;
+pub mod check;
 pub mod config;
+pub mod discovery;
 pub mod error;
+pub mod localization;
+pub mod pattern;
+pub mod status;
+pub mod suggest;
 pub mod uti;
+pub mod watch;
 
 #[cfg(target_os = "macos")]
 pub mod macos {
@@ -27,6 +40,7 @@ pub struct GlobalOptions {
     pub verbose: bool,
     pub quiet: bool,
     pub robust: bool,
+    pub refresh_apps: bool,
 }
 
 /// Initialize tracing subscriber based on global options
@@ -0,0 +1,512 @@
+//! Localized bundle names from `*.lproj/InfoPlist.strings`
+//!
+//! Finder shows an app under the `CFBundleDisplayName` (or `CFBundleName`) of
+//! the user's language, e.g. "Vorschau" for Preview in German. Those names live
+//! in a strings file inside each `.lproj` folder of the bundle's resources.
+//!
+//! Strings files come in three encodings: the old-style text format
+//! (`"key" = "value";`), usually UTF-16 but also UTF-8, and compiled binary or
+//! XML property lists. All of them are read into a plain key/value map.
+
+use crate::error::{InfatErrorExt, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::time::SystemTime;
+use tracing::debug;
+
+const STRINGS_FILE: &str = "InfoPlist.strings";
+
+/// Folder names older bundles use instead of language codes
+const LEGACY_LANGUAGES: &[(&str, &str)] = &[
+    ("english", "en"),
+    ("german", "de"),
+    ("french", "fr"),
+    ("japanese", "ja"),
+    ("spanish", "es"),
+    ("italian", "it"),
+    ("dutch", "nl"),
+];
+
+/// Why a strings file couldn't be read
+#[derive(Debug, thiserror::Error)]
+pub enum StringsError {
+    #[error("not valid UTF-8 or UTF-16 text")]
+    Encoding,
+
+    #[error("{message} on line {line}")]
+    Syntax { line: usize, message: &'static str },
+
+    #[error(transparent)]
+    Plist(#[from] plist::Error),
+}
+
+/// The names a bundle declares for one language
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalizedNames {
+    /// `CFBundleDisplayName`
+    pub display_name: Option<String>,
+    /// `CFBundleName`
+    pub name: Option<String>,
+}
+
+impl LocalizedNames {
+    /// The name Finder shows, preferring the display name
+    pub fn preferred(&self) -> Option<&str> {
+        self.display_name.as_deref().or(self.name.as_deref())
+    }
+
+    /// Every distinct name, display name first
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        let name = self
+            .name
+            .as_deref()
+            .filter(|name| Some(*name) != self.display_name.as_deref());
+        self.display_name.as_deref().into_iter().chain(name)
+    }
+}
+
+/// Parse a strings file in any of its encodings
+pub fn parse_strings(bytes: &[u8]) -> std::result::Result<HashMap<String, String>, StringsError> {
+    if bytes.starts_with(b"bplist") {
+        return Ok(string_values(plist::from_bytes(bytes)?));
+    }
+
+    let text = decode(bytes)?;
+    let trimmed = text.trim_start();
+    if trimmed.starts_with("<?xml") || trimmed.starts_with("<plist") {
+        return Ok(string_values(plist::from_reader_xml(trimmed.as_bytes())?));
+    }
+
+    Parser::new(&text).entries()
+}
+
+/// Read the `InfoPlist.strings` of a single `.lproj` folder
+pub fn read_strings(path: &Path) -> Result<HashMap<String, String>> {
+    let bytes = std::fs::read(path)?;
+    parse_strings(&bytes).plist_read_error(path)
+}
+
+/// The newest modification time among an application's Resources folder, its
+/// `.lproj` folders and their strings files, i.e. anything that would change
+/// what [`load`] returns
+pub fn modified(app_path: &Path) -> Option<SystemTime> {
+    let resources = app_path.join("Contents").join("Resources");
+    let mut newest = modified_time(&resources)?;
+
+    for entry in std::fs::read_dir(&resources).ok()?.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "lproj") {
+            continue;
+        }
+        for stamp in [
+            modified_time(&path),
+            modified_time(&path.join(STRINGS_FILE)),
+        ] {
+            newest = newest.max(stamp.unwrap_or(newest));
+        }
+    }
+
+    Some(newest)
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// The localized names of an application, keyed by `.lproj` folder name
+/// (`de`, `pt_BR`, `German`). Unreadable strings files are skipped.
+pub fn load(app_path: &Path) -> BTreeMap<String, LocalizedNames> {
+    let mut localizations = BTreeMap::new();
+
+    let Ok(entries) = std::fs::read_dir(app_path.join("Contents").join("Resources")) else {
+        return localizations;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "lproj") {
+            continue;
+        }
+        let Some(language) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        let strings_path = path.join(STRINGS_FILE);
+        if !strings_path.exists() {
+            continue;
+        }
+
+        let mut strings = match read_strings(&strings_path) {
+            Ok(strings) => strings,
+            Err(e) => {
+                debug!("Skipping {}: {}", strings_path.display(), e);
+                continue;
+            }
+        };
+
+        let names = LocalizedNames {
+            display_name: strings.remove("CFBundleDisplayName"),
+            name: strings.remove("CFBundleName"),
+        };
+        if names.preferred().is_some() {
+            localizations.insert(language.to_string(), names);
+        }
+    }
+
+    localizations
+}
+
+/// The localization that best fits a language such as `de`, `de-CH` or
+/// `pt_BR`: an exact match first, then the base language, then any region of it
+pub fn for_language<'a>(
+    localizations: &'a BTreeMap<String, LocalizedNames>,
+    language: &str,
+) -> Option<(&'a str, &'a LocalizedNames)> {
+    let wanted = language_code(language);
+    let base = base_language(&wanted);
+
+    let find = |matches: &dyn Fn(&str) -> bool| {
+        localizations
+            .iter()
+            .find(|(folder, _)| matches(&language_code(folder)))
+            .map(|(folder, names)| (folder.as_str(), names))
+    };
+
+    find(&|code| code == wanted)
+        .or_else(|| find(&|code| code == base))
+        .or_else(|| find(&|code| base_language(code) == base))
+}
+
+/// A language or `.lproj` name as a lowercase code with `_` separators
+fn language_code(language: &str) -> String {
+    let code = language.trim().to_lowercase().replace('-', "_");
+    LEGACY_LANGUAGES
+        .iter()
+        .find(|(legacy, _)| *legacy == code)
+        .map(|(_, code)| code.to_string())
+        .unwrap_or(code)
+}
+
+fn base_language(code: &str) -> &str {
+    code.split('_').next().unwrap_or(code)
+}
+
+fn string_values(dictionary: plist::Dictionary) -> HashMap<String, String> {
+    dictionary
+        .into_iter()
+        .filter_map(|(key, value)| value.into_string().map(|value| (key, value)))
+        .collect()
+}
+
+/// Decode text by its byte order mark, or by where the zero bytes of ASCII
+/// characters fall when UTF-16 has none
+fn decode(bytes: &[u8]) -> std::result::Result<String, StringsError> {
+    let utf16 = |bytes: &[u8], from: fn([u8; 2]) -> u16| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| from([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16(&units).map_err(|_| StringsError::Encoding)
+    };
+
+    match bytes {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => {
+            String::from_utf8(rest.to_vec()).map_err(|_| StringsError::Encoding)
+        }
+        [first, 0, ..] if *first != 0 => utf16(bytes, u16::from_le_bytes),
+        [0, second, ..] if *second != 0 => utf16(bytes, u16::from_be_bytes),
+        _ => String::from_utf8(bytes.to_vec()).map_err(|_| StringsError::Encoding),
+    }
+}
+
+/// Parser for the old-style text format: `"key" = "value";` pairs with C
+/// comments, optionally wrapped in braces
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn entries(mut self) -> std::result::Result<HashMap<String, String>, StringsError> {
+        let mut entries = HashMap::new();
+
+        self.skip_trivia()?;
+        let braced = self.chars.next_if_eq(&'{').is_some();
+
+        loop {
+            self.skip_trivia()?;
+            match self.chars.peek() {
+                None if braced => return Err(self.error("missing closing '}'")),
+                None => break,
+                Some('}') if braced => {
+                    self.chars.next();
+                    break;
+                }
+                Some(_) => {}
+            }
+
+            let key = self.token()?;
+            self.skip_trivia()?;
+
+            // `"key";` is shorthand for a value equal to the key
+            let value = if self.chars.next_if_eq(&'=').is_some() {
+                self.skip_trivia()?;
+                let value = self.token()?;
+                self.skip_trivia()?;
+                value
+            } else {
+                key.clone()
+            };
+
+            if self.chars.next_if_eq(&';').is_none() {
+                return Err(self.error("expected ';'"));
+            }
+            entries.insert(key, value);
+        }
+
+        self.skip_trivia()?;
+        if self.chars.peek().is_some() {
+            return Err(self.error("unexpected text after the last entry"));
+        }
+        Ok(entries)
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn error(&self, message: &'static str) -> StringsError {
+        StringsError::Syntax {
+            line: self.line,
+            message,
+        }
+    }
+
+    fn skip_trivia(&mut self) -> std::result::Result<(), StringsError> {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() || *c == '\u{FEFF}' => {
+                    self.next();
+                }
+                Some('/') => {
+                    self.next();
+                    match self.next() {
+                        Some('/') => while !matches!(self.next(), Some('\n') | None) {},
+                        Some('*') => loop {
+                            match self.next() {
+                                Some('*') if self.chars.next_if_eq(&'/').is_some() => break,
+                                Some(_) => {}
+                                None => return Err(self.error("unterminated comment")),
+                            }
+                        },
+                        _ => return Err(self.error("unexpected '/'")),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// A quoted string or a bare word
+    fn token(&mut self) -> std::result::Result<String, StringsError> {
+        if self.chars.next_if_eq(&'"').is_some() {
+            return self.quoted();
+        }
+
+        let mut word = String::new();
+        while let Some(c) = self
+            .chars
+            .next_if(|c| c.is_alphanumeric() || "_.$:/-".contains(*c))
+        {
+            word.push(c);
+        }
+
+        if word.is_empty() {
+            return Err(self.error("expected a string"));
+        }
+        Ok(word)
+    }
+
+    fn quoted(&mut self) -> std::result::Result<String, StringsError> {
+        let mut value = String::new();
+        // A high surrogate from `\U` waiting for its other half
+        let mut pending: Option<u16> = None;
+
+        loop {
+            let c = match self.next() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => break,
+                Some('\\') => match self.next() {
+                    None => return Err(self.error("unterminated string")),
+                    Some('U' | 'u') => {
+                        let unit = self.hex_unit()?;
+                        match (pending.take(), unit) {
+                            (None, 0xD800..=0xDBFF) => {
+                                pending = Some(unit);
+                                continue;
+                            }
+                            (Some(high), 0xDC00..=0xDFFF) => char::decode_utf16([high, unit])
+                                .next()
+                                .and_then(|c| c.ok())
+                                .unwrap_or(char::REPLACEMENT_CHARACTER),
+                            (_, unit) => {
+                                char::from_u32(unit.into()).unwrap_or(char::REPLACEMENT_CHARACTER)
+                            }
+                        }
+                    }
+                    Some(digit @ '0'..='7') => {
+                        let mut code = digit.to_digit(8).unwrap_or_default();
+                        for _ in 0..2 {
+                            match self.chars.next_if(|c| c.is_digit(8)) {
+                                Some(digit) => {
+                                    code = code * 8 + digit.to_digit(8).unwrap_or_default()
+                                }
+                                None => break,
+                            }
+                        }
+                        char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                    }
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('a') => '\u{7}',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{C}',
+                    Some('v') => '\u{B}',
+                    Some(other) => other,
+                },
+                Some(c) => c,
+            };
+
+            if pending.take().is_some() {
+                value.push(char::REPLACEMENT_CHARACTER);
+            }
+            value.push(c);
+        }
+
+        if pending.is_some() {
+            value.push(char::REPLACEMENT_CHARACTER);
+        }
+        Ok(value)
+    }
+
+    fn hex_unit(&mut self) -> std::result::Result<u16, StringsError> {
+        let mut unit = 0u16;
+        for _ in 0..4 {
+            let digit = self
+                .chars
+                .next_if(char::is_ascii_hexdigit)
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.error("expected four hex digits after '\\U'"))?;
+            unit = unit * 16 + digit as u16;
+        }
+        Ok(unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"/* Localized versions of Info.plist keys */
+{
+CFBundleName = "Vorschau";
+"CFBundleDisplayName" = "Vor\"schau\U00e4 \U0001";  // trailing comment
+"NSHumanReadableCopyright" = "\U00a9 2024\nAll rights";
+"Shorthand";
+}
+"#;
+
+    fn utf16(text: &str, bom: bool, from: fn(u16) -> [u8; 2]) -> Vec<u8> {
+        let units = bom.then_some(0xFEFF).into_iter().chain(text.encode_utf16());
+        units.flat_map(from).collect()
+    }
+
+    #[test]
+    fn parses_old_style_text_in_every_encoding() {
+        for bytes in [
+            TEXT.as_bytes().to_vec(),
+            utf16(TEXT, true, u16::to_le_bytes),
+            utf16(TEXT, true, u16::to_be_bytes),
+            utf16(TEXT, false, u16::to_le_bytes),
+        ] {
+            let strings = parse_strings(&bytes).unwrap();
+            assert_eq!(strings["CFBundleName"], "Vorschau");
+            assert_eq!(strings["CFBundleDisplayName"], "Vor\"schauä \u{1}");
+            assert_eq!(strings["NSHumanReadableCopyright"], "© 2024\nAll rights");
+            assert_eq!(strings["Shorthand"], "Shorthand");
+        }
+
+        assert!(matches!(
+            parse_strings(b"\"CFBundleName\" = \"Vorschau\"\n\"Other\" = \"x\";"),
+            Err(StringsError::Syntax { line: 2, .. })
+        ));
+        assert!(parse_strings(b"\"Unterminated = \"x\";").is_err());
+    }
+
+    #[test]
+    fn parses_compiled_property_lists() {
+        let mut dictionary = plist::Dictionary::new();
+        dictionary.insert("CFBundleDisplayName".into(), "プレビュー".into());
+        dictionary.insert("LSHasLocalizedDisplayName".into(), true.into());
+
+        let mut binary = Vec::new();
+        plist::to_writer_binary(&mut binary, &dictionary).unwrap();
+        let mut xml = Vec::new();
+        plist::to_writer_xml(&mut xml, &dictionary).unwrap();
+
+        for bytes in [binary, xml] {
+            let strings = parse_strings(&bytes).unwrap();
+            assert_eq!(strings.len(), 1);
+            assert_eq!(strings["CFBundleDisplayName"], "プレビュー");
+        }
+    }
+
+    #[test]
+    fn picks_the_closest_localization() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let resources = tmp.path().join("Contents").join("Resources");
+        for (folder, contents) in [
+            ("German.lproj", "CFBundleName = \"Vorschau\";"),
+            ("pt_BR.lproj", "CFBundleDisplayName = \"Pré-Visualização\";"),
+            ("fr.lproj", "CFBundleName = \"Aperçu\";"),
+            ("en.lproj", "NSHumanReadableCopyright = \"Nobody\";"),
+            ("Base.lproj", "CFBundleName = broken"),
+        ] {
+            let folder = resources.join(folder);
+            std::fs::create_dir_all(&folder).unwrap();
+            std::fs::write(folder.join(STRINGS_FILE), contents).unwrap();
+        }
+
+        let localizations = load(tmp.path());
+        assert_eq!(
+            localizations.keys().collect::<Vec<_>>(),
+            ["German", "fr", "pt_BR"]
+        );
+
+        let name = |language: &str| {
+            for_language(&localizations, language).and_then(|(_, names)| names.preferred())
+        };
+        assert_eq!(name("de"), Some("Vorschau"));
+        assert_eq!(name("de-CH"), Some("Vorschau"));
+        assert_eq!(name("FR_ca"), Some("Aperçu"));
+        assert_eq!(name("pt"), Some("Pré-Visualização"));
+        assert_eq!(name("en"), None);
+    }
+}
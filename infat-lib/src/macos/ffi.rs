@@ -51,8 +51,11 @@ extern "C" {
         inUTI: CFStringRef,
         inTagClass: CFStringRef,
     ) -> CFStringRef;
+
+    pub fn UTTypeCopyAllTagsWithClass(inUTI: CFStringRef, inTagClass: CFStringRef) -> CFArrayRef;
 }
 
 // UTI tag classes
 pub const K_UT_TAG_CLASS_FILENAME_EXTENSION: &str = "public.filename-extension";
 pub const K_UT_TAG_CLASS_MIME_TYPE: &str = "public.mime-type";
+pub const K_UT_TAG_CLASS_OSTYPE: &str = "com.apple.ostype";
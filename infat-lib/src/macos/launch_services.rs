@@ -1,7 +1,10 @@
 //! High-level Launch Services API wrappers
 
 use super::ffi::*;
-use crate::error::{InfatError, Result};
+use crate::{
+    error::{InfatError, Result},
+    uti::{TagClass, TypeTags},
+};
 use core_foundation::{array::CFArray, base::TCFType, string::CFString, url::CFURL};
 use std::path::Path;
 use tracing::debug;
@@ -167,48 +170,116 @@ pub fn register_application<P: AsRef<Path>>(app_path: P) -> Result<()> {
 pub fn get_uti_for_extension(extension: &str) -> Result<String> {
     debug!("Getting UTI for extension: {}", extension);
 
-    let cf_tag_class = CFString::new(K_UT_TAG_CLASS_FILENAME_EXTENSION);
-    let cf_extension = CFString::new(extension);
+    let uti = preferred_identifier_for_tag(K_UT_TAG_CLASS_FILENAME_EXTENSION, extension)
+        .ok_or_else(|| InfatError::CouldNotDeriveUTI {
+            extension: extension.to_string(),
+        })?;
+
+    debug!("UTI for extension '{}': {}", extension, uti);
+    Ok(uti)
+}
+
+/// Get the preferred file extension for a UTI
+pub fn get_extension_for_uti(uti: &str) -> Result<Option<String>> {
+    debug!("Getting extension for UTI: {}", uti);
+
+    let extension = preferred_tag_for_uti(uti, K_UT_TAG_CLASS_FILENAME_EXTENSION);
+    if let Some(extension) = &extension {
+        debug!("Extension for UTI '{}': {}", uti, extension);
+    }
+    Ok(extension)
+}
+
+/// Get the UTI for a MIME type
+pub fn get_uti_for_mime_type(mime_type: &str) -> Result<String> {
+    debug!("Getting UTI for MIME type: {}", mime_type);
+
+    let uti =
+        preferred_identifier_for_tag(K_UT_TAG_CLASS_MIME_TYPE, mime_type).ok_or_else(|| {
+            InfatError::CouldNotDeriveUTIForMimeType {
+                mime_type: mime_type.to_string(),
+            }
+        })?;
+
+    debug!("UTI for MIME type '{}': {}", mime_type, uti);
+    Ok(uti)
+}
+
+/// Get the preferred MIME type for a UTI
+pub fn get_mime_type_for_uti(uti: &str) -> Result<Option<String>> {
+    debug!("Getting MIME type for UTI: {}", uti);
+
+    let mime_type = preferred_tag_for_uti(uti, K_UT_TAG_CLASS_MIME_TYPE);
+    if let Some(mime_type) = &mime_type {
+        debug!("MIME type for UTI '{}': {}", uti, mime_type);
+    }
+    Ok(mime_type)
+}
+
+/// Get every tag of one class that Launch Services maps to a UTI
+pub fn get_all_tags_for_uti(uti: &str, tag_class: &str) -> Result<Vec<String>> {
+    debug!("Getting all {} tags for UTI: {}", tag_class, uti);
+
+    let cf_uti = CFString::new(uti);
+    let cf_tag_class = CFString::new(tag_class);
+    let cf_array = unsafe {
+        UTTypeCopyAllTagsWithClass(
+            cf_uti.as_concrete_TypeRef(),
+            cf_tag_class.as_concrete_TypeRef(),
+        )
+    };
+
+    if cf_array.is_null() {
+        return Ok(Vec::new());
+    }
+
+    let array = unsafe { CFArray::<CFString>::wrap_under_create_rule(cf_array) };
+    Ok(array.iter().map(|tag| tag.to_string()).collect())
+}
+
+/// Get every extension, MIME type and OSType Launch Services maps to a UTI
+pub fn get_tags_for_uti(uti: &str) -> Result<TypeTags> {
+    let mut tags = TypeTags::default();
+    for class in TagClass::ALL {
+        tags.extend(class, get_all_tags_for_uti(uti, class.identifier())?);
+    }
+    Ok(tags)
+}
+
+fn preferred_identifier_for_tag(tag_class: &str, tag: &str) -> Option<String> {
+    let cf_tag_class = CFString::new(tag_class);
+    let cf_tag = CFString::new(tag);
     let cf_conforming_to = CFString::new("");
 
     let cf_uti = unsafe {
         UTTypeCreatePreferredIdentifierForTag(
             cf_tag_class.as_concrete_TypeRef(),
-            cf_extension.as_concrete_TypeRef(),
+            cf_tag.as_concrete_TypeRef(),
             cf_conforming_to.as_concrete_TypeRef(),
         )
     };
 
     if cf_uti.is_null() {
-        return Err(InfatError::CouldNotDeriveUTI {
-            extension: extension.to_string(),
-        });
+        return None;
     }
 
-    let uti = unsafe { CFString::wrap_under_create_rule(cf_uti) }.to_string();
-    debug!("UTI for extension '{}': {}", extension, uti);
-    Ok(uti)
+    Some(unsafe { CFString::wrap_under_create_rule(cf_uti) }.to_string())
 }
 
-/// Get the preferred file extension for a UTI
-pub fn get_extension_for_uti(uti: &str) -> Result<Option<String>> {
-    debug!("Getting extension for UTI: {}", uti);
-
+fn preferred_tag_for_uti(uti: &str, tag_class: &str) -> Option<String> {
     let cf_uti = CFString::new(uti);
-    let cf_tag_class = CFString::new(K_UT_TAG_CLASS_FILENAME_EXTENSION);
+    let cf_tag_class = CFString::new(tag_class);
 
-    let cf_extension = unsafe {
+    let cf_tag = unsafe {
         UTTypeCopyPreferredTagWithClass(
             cf_uti.as_concrete_TypeRef(),
             cf_tag_class.as_concrete_TypeRef(),
         )
     };
 
-    if cf_extension.is_null() {
-        return Ok(None);
+    if cf_tag.is_null() {
+        return None;
     }
 
-    let extension = unsafe { CFString::wrap_under_create_rule(cf_extension) }.to_string();
-    debug!("Extension for UTI '{}': {}", uti, extension);
-    Ok(Some(extension))
+    Some(unsafe { CFString::wrap_under_create_rule(cf_tag) }.to_string())
 }
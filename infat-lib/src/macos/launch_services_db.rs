@@ -1,10 +1,14 @@
-//! Launch Services database parsing for the init command
+//! Launch Services database parsing for the init command and reverse lookups
 
+use crate::capability::{Claim, HandlerRole, OwnedHandler};
+use crate::config::{Config, Settings};
 use crate::error::{InfatError, Result};
 use crate::macos::workspace::{self, resolve_to_bundle_id};
+use crate::uti::DynamicUti;
 use plist::Value;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use tracing::{debug, info, warn};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -43,18 +47,23 @@ pub struct LaunchServicesDatabase {
     pub handlers: Vec<LaunchServicesHandler>,
 }
 
-/// Read the Launch Services database from the user's preferences
-pub fn read_launch_services_database() -> Result<LaunchServicesDatabase> {
+/// Location of the user's Launch Services handler database
+pub fn database_path() -> Result<PathBuf> {
     let home = dirs::home_dir().ok_or_else(|| InfatError::LaunchServicesError {
         message: "Could not determine home directory".to_string(),
     })?;
 
     // Needs to be consistent across systems
-    let ls_path = home
+    Ok(home
         .join("Library")
         .join("Preferences")
         .join("com.apple.LaunchServices")
-        .join("com.apple.launchservices.secure.plist");
+        .join("com.apple.launchservices.secure.plist"))
+}
+
+/// Read the Launch Services database from the user's preferences
+pub fn read_launch_services_database() -> Result<LaunchServicesDatabase> {
+    let ls_path = database_path()?;
 
     debug!(
         "Reading Launch Services database from: {}",
@@ -91,18 +100,90 @@ pub fn read_launch_services_database() -> Result<LaunchServicesDatabase> {
     Ok(db)
 }
 
-/// Generate a config from the current Launch Services database
-pub fn generate_config_from_launch_services(robust: bool) -> Result<crate::config::Config> {
+/// Every `LSHandlers` entry naming the bundle ID in any role, across
+/// content types, extensions, MIME types and URL schemes
+pub fn handlers_for_bundle_id(db: &LaunchServicesDatabase, bundle_id: &str) -> Vec<OwnedHandler> {
+    db.handlers
+        .iter()
+        .filter_map(|handler| {
+            let roles: Vec<HandlerRole> = [
+                (HandlerRole::All, &handler.role_all),
+                (HandlerRole::Viewer, &handler.role_viewer),
+                (HandlerRole::Editor, &handler.role_editor),
+            ]
+            .into_iter()
+            .filter(|(_, handler_id)| {
+                handler_id
+                    .as_deref()
+                    .is_some_and(|handler_id| handler_id.eq_ignore_ascii_case(bundle_id))
+            })
+            .map(|(role, _)| role)
+            .collect();
+
+            if roles.is_empty() {
+                return None;
+            }
+
+            let claim = handler_claim(handler);
+            if claim.is_none() {
+                debug!("Skipping handler entry with no content type or scheme");
+            }
+            claim.map(|claim| OwnedHandler { claim, roles })
+        })
+        .collect()
+}
+
+/// What a handler entry is for. A dynamic type is only an encoded tag, which
+/// reads better as the tag itself; one with no extension or MIME type stays a
+/// type.
+fn handler_claim(handler: &LaunchServicesHandler) -> Option<Claim> {
+    if let Some(scheme) = &handler.url_scheme {
+        return Some(Claim::Scheme(scheme.clone()));
+    }
+
+    let (tag_class, tag) = match handler.content_type.as_deref() {
+        Some(content_type) => match DynamicUti::decode(content_type)
+            .as_ref()
+            .and_then(DynamicUti::handler_tag)
+        {
+            Some((class, tag)) => {
+                debug!(
+                    "Decoded dynamic type {} to {} {}",
+                    content_type,
+                    class.identifier(),
+                    tag
+                );
+                (class.identifier(), tag.to_string())
+            }
+            None => return Some(Claim::Type(content_type.to_string())),
+        },
+        None => (
+            handler.content_tag_class.as_deref()?,
+            handler.content_tag.clone()?,
+        ),
+    };
+
+    match tag_class {
+        "public.filename-extension" => Some(Claim::Extension(tag)),
+        "public.mime-type" => Some(Claim::Mime(tag)),
+        _ => None,
+    }
+}
+
+/// Generate a config from the current Launch Services database, naming apps
+/// as found in the given search paths
+pub fn generate_config_from_launch_services(robust: bool, settings: &Settings) -> Result<Config> {
     let db = read_launch_services_database()?;
 
     let mut extensions = HashMap::new();
     let mut schemes = HashMap::new();
     let mut types = HashMap::new();
+    let mut mime = HashMap::new();
     let mut skipped_count = 0;
     let mut processed_count = 0;
 
     for handler in db.handlers {
-        if let Some(bundle_id) = handler.role_all {
+        if let Some(bundle_id) = handler.role_all.as_deref() {
             // Skip malformed entries
             if bundle_id == "-" {
                 debug!("Skipping malformed handler entry");
@@ -111,28 +192,28 @@ pub fn generate_config_from_launch_services(robust: bool) -> Result<crate::confi
             }
 
             // Skip system services
-            if crate::macos::workspace::is_system_service(&bundle_id) {
+            if crate::macos::workspace::is_system_service(bundle_id) {
                 debug!("Skipping system service: {}", bundle_id);
                 skipped_count += 1;
                 continue;
             }
 
             // Canonicalize the id (There's sometimes a difference between the id the application provides to launchservices and the one it'll key itself as to be identified as)
-            let canonical_id = match resolve_to_bundle_id(&bundle_id) {
+            let canonical_id = match resolve_to_bundle_id(bundle_id, settings) {
                 Ok(id) => id,
-                Err(_) => {
+                Err(e) => {
                     // couldn’t resolve, so skip or warn
                     if robust {
                         warn!("Skipping unresolved bundle id {:?}", bundle_id);
                         skipped_count += 1;
                         continue;
                     } else {
-                        return Err(InfatError::ApplicationNotFound { name: bundle_id });
+                        return Err(e);
                     }
                 }
             };
 
-            let app_name = match workspace::get_app_name_from_bundle_id(&canonical_id) {
+            let app_name = match workspace::get_app_name_from_bundle_id(&canonical_id, settings) {
                 Ok(name) => name,
                 Err(e) => {
                     if robust {
@@ -146,20 +227,22 @@ pub fn generate_config_from_launch_services(robust: bool) -> Result<crate::confi
             };
 
             // Process different handler types
-            if let Some(scheme) = handler.url_scheme {
-                schemes.insert(scheme, app_name);
-                processed_count += 1;
-            } else if let Some(content_type) = handler.content_type {
-                types.insert(content_type, app_name);
-                processed_count += 1;
-            } else if let Some(tag_class) = handler.content_tag_class {
-                if tag_class == "public.filename-extension" {
-                    if let Some(ext) = handler.content_tag {
-                        extensions.insert(ext, app_name);
-                        processed_count += 1;
-                    }
+            match handler_claim(&handler) {
+                Some(Claim::Scheme(scheme)) => {
+                    schemes.insert(scheme, app_name.into());
+                }
+                Some(Claim::Type(content_type)) => {
+                    types.insert(content_type, app_name.into());
+                }
+                Some(Claim::Extension(extension)) => {
+                    extensions.insert(extension, app_name.into());
+                }
+                Some(Claim::Mime(mime_type)) => {
+                    mime.insert(mime_type, app_name.into());
                 }
+                None => continue,
             }
+            processed_count += 1;
         }
     }
 
@@ -168,9 +251,11 @@ pub fn generate_config_from_launch_services(robust: bool) -> Result<crate::confi
         processed_count, skipped_count
     );
 
-    Ok(crate::config::Config {
+    Ok(Config {
         extensions,
         schemes,
         types,
+        mime,
+        ..Default::default()
     })
 }
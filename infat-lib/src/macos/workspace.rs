@@ -1,6 +1,12 @@
 //! NSWorkspace integration for app discovery and management
 
-use crate::error::{InfatError, Result};
+use crate::{
+    app_index::{self, IndexedApp},
+    bundle::BundleInfo,
+    config::Settings,
+    error::{InfatError, Result},
+    suggest::Suggestions,
+};
 use objc::{class, msg_send, runtime::Object, sel, sel_impl};
 use objc_foundation::{INSString, NSString};
 use std::path::{Path, PathBuf};
@@ -16,9 +22,47 @@ unsafe fn shared_workspace() -> *mut Object {
     msg_send![workspace_class, sharedWorkspace]
 }
 
-/// Find application paths for a bundle identifier
-pub fn get_app_paths_for_bundle_id(bundle_id: &str) -> Result<Vec<PathBuf>> {
-    debug!("Finding app paths for bundle ID: {}", bundle_id);
+/// Find every installed copy of a bundle identifier, preferred copy first
+pub fn get_app_paths_for_bundle_id(bundle_id: &str, settings: &Settings) -> Result<Vec<PathBuf>> {
+    Ok(installed_copies(bundle_id, None, settings)?
+        .into_iter()
+        .map(|app| app.path)
+        .collect())
+}
+
+/// Every installed copy of a bundle identifier, ordered by the selection
+/// policy in [`app_index`]. Besides the copies in the search paths this
+/// includes the one Launch Services prefers and the explicit path, either of
+/// which may lie outside them.
+pub fn installed_copies(
+    bundle_id: &str,
+    explicit: Option<&Path>,
+    settings: &Settings,
+) -> Result<Vec<IndexedApp>> {
+    let mut copies: Vec<IndexedApp> = app_index::shared(&settings.search_paths())?
+        .copies(bundle_id, settings.prefer_copy)
+        .into_iter()
+        .cloned()
+        .collect();
+
+    let extra = [
+        launch_services_path(bundle_id),
+        explicit.map(Path::to_path_buf),
+    ];
+    for path in extra.into_iter().flatten() {
+        if !copies.iter().any(|app| app.path == path) {
+            copies.push(IndexedApp::read(path));
+        }
+    }
+
+    copies.sort_by(|a, b| app_index::compare_copies(a, b, explicit, settings.prefer_copy));
+    debug!("{} installed copies of {}", copies.len(), bundle_id);
+    Ok(copies)
+}
+
+/// The copy of an app NSWorkspace would launch for a bundle identifier
+fn launch_services_path(bundle_id: &str) -> Option<PathBuf> {
+    debug!("Finding app path for bundle ID: {}", bundle_id);
 
     unsafe {
         let workspace = shared_workspace();
@@ -28,7 +72,7 @@ pub fn get_app_paths_for_bundle_id(bundle_id: &str) -> Result<Vec<PathBuf>> {
 
         if cf_url.is_null() {
             debug!("No application found for bundle ID: {}", bundle_id);
-            return Ok(Vec::new());
+            return None;
         }
 
         let ns_path: *mut NSString = msg_send![cf_url, path];
@@ -36,45 +80,40 @@ pub fn get_app_paths_for_bundle_id(bundle_id: &str) -> Result<Vec<PathBuf>> {
         let path = PathBuf::from(path_str);
 
         debug!("Found app path for {}: {}", bundle_id, path.display());
-        Ok(vec![path])
+        Some(path)
     }
 }
 
 /// Get bundle identifier from application path
-pub fn get_bundle_id_from_app_path<P: AsRef<Path>>(app_path: P) -> Result<String> {
+pub fn get_bundle_id_from_app_path<P: AsRef<Path>>(
+    app_path: P,
+    settings: &Settings,
+) -> Result<String> {
     let path = app_path.as_ref();
     debug!("Getting bundle ID for app: {}", path.display());
 
-    // Read Info.plist from the app bundle
-    let info_plist_path = path.join("Contents").join("Info.plist");
-
-    if !info_plist_path.exists() {
-        return Err(InfatError::InfoPlistNotFound {
-            app_path: path.to_path_buf(),
-        });
+    if let Some(bundle_id) = app_index::shared(&settings.search_paths())?
+        .find_by_path(path)
+        .and_then(|app| app.bundle_id.clone())
+    {
+        debug!("Bundle ID for {} (indexed): {}", path.display(), bundle_id);
+        return Ok(bundle_id);
     }
 
-    let plist_data = std::fs::read(&info_plist_path)?;
-    let plist: plist::Value =
-        plist::from_bytes(&plist_data).map_err(|e| InfatError::PlistReadError {
-            path: info_plist_path.clone(),
-            source: Box::new(e),
-        })?;
-
-    let bundle_id = plist
-        .as_dictionary()
-        .and_then(|dict| dict.get("CFBundleIdentifier"))
-        .and_then(|val| val.as_string())
-        .ok_or_else(|| InfatError::BundleIdNotFound {
-            path: path.to_path_buf(),
-        })?;
+    let bundle_id =
+        BundleInfo::load(path)?
+            .identifier
+            .clone()
+            .ok_or_else(|| InfatError::BundleIdNotFound {
+                path: path.to_path_buf(),
+            })?;
 
     debug!("Bundle ID for {}: {}", path.display(), bundle_id);
-    Ok(bundle_id.to_string())
+    Ok(bundle_id)
 }
 
 /// Get app name (display name) from bundle ID
-pub fn get_app_name_from_bundle_id(bundle_id: &str) -> Result<String> {
+pub fn get_app_name_from_bundle_id(bundle_id: &str, settings: &Settings) -> Result<String> {
     debug!("Getting app name for bundle ID: {}", bundle_id);
 
     // Check for system services
@@ -84,42 +123,28 @@ pub fn get_app_name_from_bundle_id(bundle_id: &str) -> Result<String> {
         });
     }
 
-    let app_paths = get_app_paths_for_bundle_id(bundle_id)?;
+    let roots = settings.search_paths();
+    let app_paths = get_app_paths_for_bundle_id(bundle_id, settings)?;
 
     let app_path = app_paths
         .first()
         .ok_or_else(|| InfatError::ApplicationNotFound {
             name: bundle_id.to_string(),
+            suggestions: Suggestions::installed_apps(bundle_id, &roots),
         })?;
 
-    // Read display name from Info.plist
-    let info_plist_path = app_path.join("Contents").join("Info.plist");
-    let plist_data = std::fs::read(&info_plist_path)?;
-    let plist: plist::Value =
-        plist::from_bytes(&plist_data).map_err(|e| InfatError::PlistReadError {
-            path: info_plist_path.clone(),
-            source: Box::new(e),
-        })?;
-
-    let dict = plist
-        .as_dictionary()
-        .ok_or_else(|| InfatError::PlistReadError {
-            path: info_plist_path.clone(),
-            source: "Info.plist root is not a dictionary".into(),
-        })?;
-
-    // Try CFBundleDisplayName first, then CFBundleName
-    let app_name = dict
-        .get("CFBundleDisplayName")
-        .or_else(|| dict.get("CFBundleName"))
-        .and_then(|val| val.as_string())
-        .unwrap_or("Unknown");
+    if let Some(app) = app_index::shared(&roots)?.find_by_path(app_path) {
+        debug!(
+            "App name for {} (indexed): {}",
+            bundle_id,
+            app.preferred_name()
+        );
+        return Ok(app.preferred_name().to_string());
+    }
 
-    let authoritative_id = dict
-        .get("CFBundleIdentifier")
-        .expect("Required for any registered app")
-        .as_string()
-        .unwrap_or("Unknown");
+    let info = BundleInfo::load(app_path)?;
+    let app_name = info.preferred_name().unwrap_or("Unknown");
+    let authoritative_id = info.identifier.as_deref().unwrap_or("Unknown");
 
     // Prioritize the pretty name but fallbak on the authoritative
     if app_name != authoritative_id {
@@ -130,68 +155,23 @@ pub fn get_app_name_from_bundle_id(bundle_id: &str) -> Result<String> {
     Ok(app_name.to_string())
 }
 
-/// Find applications in standard directories
-pub fn find_applications() -> Result<Vec<PathBuf>> {
-    debug!("Searching for applications in standard directories");
-
-    let mut search_paths = vec![
-        Path::new("/Applications").to_path_buf(),
-        Path::new("/System/Applications").to_path_buf(),
-        Path::new("/System/Library/CoreServices/Applications").to_path_buf(),
-        Path::new(&std::env::var("HOME").unwrap_or_default()).join("Applications")
-    ];
-
-    let mut apps = Vec::new();
-
-    while search_paths.len() > 0 {
-        let path = search_paths.pop().unwrap();
-        if !path.exists() {
-            debug!("Skipping non-existent path: {}", path.to_str().unwrap());
-            continue;
-        }
+/// Find applications in the search paths
+pub fn find_applications(settings: &Settings) -> Result<Vec<PathBuf>> {
+    debug!("Searching for applications in the search paths");
 
-        match std::fs::read_dir(&path) {
-            Ok(entries) => {
-                let mut found_count = 0;
-                for entry in entries.flatten() {
-                    let entry_path = entry.path();
-
-                    // Follow symlinks to get the actual target
-                    let resolved_path = if entry_path.is_symlink() {
-                        match std::fs::canonicalize(&entry_path) {
-                            Ok(canonical) => canonical,
-                            Err(_) => continue, // Skip broken symlinks
-                        }
-                    } else {
-                        entry_path.clone()
-                    };
-
-                    if resolved_path.extension().is_some_and(|ext| ext == "app") {
-                        apps.push(entry_path);
-                        found_count += 1;
-                    } else if resolved_path.is_dir() {
-                        search_paths.push(resolved_path);
-                    }
-                }
-                debug!("Found {} apps in {}", found_count, path.to_str().unwrap());
-            }
-            Err(e) => {
-                debug!("Could not read directory {}: {}", path.to_str().unwrap(), e);
-            }
-        }
-    }
+    let apps = app_index::shared(&settings.search_paths())?.paths();
 
     debug!("Total applications found: {}", apps.len());
     Ok(apps)
 }
 
 /// Find application by name or bundle ID
-pub fn find_application(name_or_bundle_id: &str) -> Result<Option<PathBuf>> {
+pub fn find_application(name_or_bundle_id: &str, settings: &Settings) -> Result<Option<PathBuf>> {
     debug!("Finding application: {}", name_or_bundle_id);
 
     // If it looks like a bundle ID, try that first
     if name_or_bundle_id.contains('.') {
-        if let Ok(paths) = get_app_paths_for_bundle_id(name_or_bundle_id) {
+        if let Ok(paths) = get_app_paths_for_bundle_id(name_or_bundle_id, settings) {
             if let Some(path) = paths.first() {
                 return Ok(Some(path.clone()));
             }
@@ -204,17 +184,15 @@ pub fn find_application(name_or_bundle_id: &str) -> Result<Option<PathBuf>> {
         return Ok(Some(path));
     }
 
-    // Search by name in standard directories
-    let apps = find_applications()?;
-    for app_path in apps {
-        let app_name = app_path
-            .file_stem()
-            .and_then(|stem| stem.to_str())
-            .unwrap_or("");
+    // Search by name (and bundle ID, for apps Launch Services doesn't know) in the index
+    let index = app_index::shared(&settings.search_paths())?;
+    let found = match index.find_by_name(name_or_bundle_id, settings.prefer_copy)? {
+        Some(app) => Some(app),
+        None => index.find_by_bundle_id(name_or_bundle_id, settings.prefer_copy),
+    };
 
-        if app_name.eq_ignore_ascii_case(name_or_bundle_id) {
-            return Ok(Some(app_path));
-        }
+    if let Some(app) = found {
+        return Ok(Some(app.path.clone()));
     }
 
     debug!("Application not found: {}", name_or_bundle_id);
@@ -229,15 +207,20 @@ pub fn is_system_service(bundle_id: &str) -> bool {
             || bundle_id.contains("daemon"))
 }
 
+/// Find an application by name, bundle ID or path, failing with suggestions
+/// when nothing matches
+pub fn resolve_application(name_or_bundle_id: &str, settings: &Settings) -> Result<PathBuf> {
+    find_application(name_or_bundle_id, settings)?.ok_or_else(|| InfatError::ApplicationNotFound {
+        name: name_or_bundle_id.to_string(),
+        suggestions: Suggestions::installed_apps(name_or_bundle_id, &settings.search_paths()),
+    })
+}
+
 /// Resolve app name or bundle ID to a bundle ID
-pub fn resolve_to_bundle_id(name_or_bundle_id: &str) -> Result<String> {
+pub fn resolve_to_bundle_id(name_or_bundle_id: &str, settings: &Settings) -> Result<String> {
     debug!("Resolving to bundle ID: {}", name_or_bundle_id);
 
     // Find the application and get its bundle ID
-    let app_path =
-        find_application(name_or_bundle_id)?.ok_or_else(|| InfatError::ApplicationNotFound {
-            name: name_or_bundle_id.to_string(),
-        })?;
-
-    get_bundle_id_from_app_path(app_path)
+    let app_path = resolve_application(name_or_bundle_id, settings)?;
+    get_bundle_id_from_app_path(app_path, settings)
 }
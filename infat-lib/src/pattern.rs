@@ -0,0 +1,157 @@
+//! Extension keys and filename patterns
+//!
+//! Launch Services can only associate a handler with a type, and it picks the
+//! type of a file from the part of its name after the last dot. Config keys
+//! are mapped onto that model with these rules:
+//!
+//! 1. Extensions are trimmed, lowercased and lose any leading dots, so `.MD`,
+//!    `md` and `*.md` all mean the same thing.
+//! 2. A compound extension such as `tar.gz` or `d.ts` only resolves when
+//!    `[declare]` or a known type claims the whole suffix. Anything else would
+//!    change the handler for every `.gz` or `.ts` file.
+//! 3. In `[files]`, a `*.suffix` pattern is the extension `suffix` and follows
+//!    rules 1 and 2.
+//! 4. Exact filenames (`Makefile`, `.env`, `index.html`) and any other glob
+//!    can't be represented, and are reported instead of applied.
+
+use crate::error::{InfatError, Result};
+use crate::uti::UtiGraph;
+
+const GLOB_CHARACTERS: &[char] = &['*', '?', '[', ']', '{', '}'];
+
+/// The canonical form of an extension key: `.Tar.GZ` becomes `tar.gz`
+pub fn normalize_extension(extension: &str) -> String {
+    let extension = extension.trim();
+    let extension = extension.strip_prefix("*.").unwrap_or(extension);
+    extension.trim_start_matches('.').to_lowercase()
+}
+
+/// The part Launch Services actually matches for a compound extension, e.g.
+/// `gz` for `tar.gz`, or `None` for a simple one
+pub fn compound_suffix(extension: &str) -> Option<&str> {
+    extension.rsplit_once('.').map(|(_, last)| last)
+}
+
+/// The type claiming a whole compound extension, or why it can't be represented
+pub fn compound_uti(extension: &str, graph: &UtiGraph) -> Result<String> {
+    if let Some(declaration) = graph.preferred_type_for_extension(extension) {
+        return Ok(declaration.identifier.clone());
+    }
+
+    let last = compound_suffix(extension).unwrap_or(extension);
+    Err(InfatError::UnrepresentablePattern {
+        pattern: format!(".{extension}"),
+        reason: format!(
+            "Launch Services only matches the last extension, so this would apply to every .{last} file; declare a type for it in [declare] if one exists"
+        ),
+    })
+}
+
+/// A `[files]` key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilePattern {
+    /// `*.md` or `*.tar.gz`: every file with that extension
+    Extension(String),
+    /// `Makefile`, `.env`: one exact filename
+    Filename(String),
+    /// Any other glob, e.g. `Dockerfile.*`
+    Glob(String),
+}
+
+impl FilePattern {
+    pub fn parse(pattern: &str) -> Self {
+        let pattern = pattern.trim();
+
+        match pattern.strip_prefix("*.") {
+            Some(suffix) if !suffix.is_empty() && !suffix.contains(GLOB_CHARACTERS) => {
+                Self::Extension(normalize_extension(suffix))
+            }
+            _ if pattern.contains(GLOB_CHARACTERS) => Self::Glob(pattern.to_string()),
+            _ => Self::Filename(pattern.to_string()),
+        }
+    }
+
+    /// The extension this pattern stands for, or why macOS can't represent it
+    pub fn extension(&self) -> Result<&str> {
+        let (pattern, reason) = match self {
+            Self::Extension(extension) => return Ok(extension),
+            Self::Filename(name) => {
+                // Like `Path::extension`, a leading dot doesn't start an extension
+                let reason = match name.rsplit_once('.') {
+                    Some((stem, extension)) if !stem.is_empty() && !extension.is_empty() => {
+                        let extension = extension.to_lowercase();
+                        format!(
+                            "macOS picks handlers by type, not by name, so this would apply to every .{extension} file; use '*.{extension}' if that's intended"
+                        )
+                    }
+                    _ => "files without an extension have no type of their own".to_string(),
+                };
+                (name, reason)
+            }
+            Self::Glob(glob) => (
+                glob,
+                "only '*.extension' patterns can be matched".to_string(),
+            ),
+        };
+
+        Err(InfatError::UnrepresentablePattern {
+            pattern: pattern.clone(),
+            reason,
+        })
+    }
+}
+
+impl std::fmt::Display for FilePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Extension(extension) => write!(f, "*.{extension}"),
+            Self::Filename(pattern) | Self::Glob(pattern) => write!(f, "{pattern}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_extensions() {
+        assert_eq!(normalize_extension(" .MD "), "md");
+        assert_eq!(normalize_extension("..Tar.GZ"), "tar.gz");
+        assert_eq!(normalize_extension("*.d.ts"), "d.ts");
+        assert_eq!(compound_suffix("tar.gz"), Some("gz"));
+        assert_eq!(compound_suffix("md"), None);
+    }
+
+    #[test]
+    fn resolves_file_patterns() {
+        let extension = |pattern: &str| FilePattern::parse(pattern).extension().map(str::to_string);
+
+        assert_eq!(extension("*.MD").unwrap(), "md");
+        assert_eq!(extension("*.tar.gz").unwrap(), "tar.gz");
+        assert_eq!(
+            FilePattern::parse("Dockerfile.*"),
+            FilePattern::Glob("Dockerfile.*".into())
+        );
+
+        for unrepresentable in ["Makefile", "Dockerfile", ".env", "index.html", "*.*", "*"] {
+            assert!(
+                matches!(
+                    extension(unrepresentable),
+                    Err(InfatError::UnrepresentablePattern { .. })
+                ),
+                "{unrepresentable} should not be representable"
+            );
+        }
+    }
+
+    #[test]
+    fn resolves_compound_extensions_only_when_declared() {
+        let graph = UtiGraph::system();
+        assert!(compound_uti("tar.gz", graph).is_err());
+        assert!(matches!(
+            compound_uti("d.ts", graph),
+            Err(InfatError::UnrepresentablePattern { reason, .. }) if reason.contains(".ts file")
+        ));
+    }
+}
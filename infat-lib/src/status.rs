@@ -0,0 +1,213 @@
+//! Drift detection between a configuration and the live Launch Services handlers
+//!
+//! The report types are plain data; only `check_config`, which fills them in
+//! from Launch Services, is macOS-only.
+
+use crate::config::EntryKind;
+#[cfg(target_os = "macos")]
+use crate::{
+    association::{self, HandlerTarget},
+    config::{Config, ConfigEntry, Settings},
+    error::Result,
+    macos::workspace,
+};
+use serde::Serialize;
+#[cfg(target_os = "macos")]
+use tracing::debug;
+
+/// How a config entry compares with the system
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryState {
+    /// The configured app is the current handler
+    Matches,
+    /// Another app (or no app) is the current handler
+    Drifted,
+    /// The entry could not be checked, e.g. because the app isn't installed
+    Unknown { reason: String },
+}
+
+/// The status of a single config entry
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryStatus {
+    pub kind: EntryKind,
+    pub key: String,
+    /// The user-defined supertype the entry was expanded from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supertype: Option<String>,
+    /// The handler the entry is stored under, e.g. `public.plain-text` or `http://`
+    pub target: Option<String>,
+    pub expected_app: String,
+    pub expected_bundle_id: Option<String>,
+    pub actual_app: Option<String>,
+    pub actual_bundle_id: Option<String>,
+    pub state: EntryState,
+}
+
+impl EntryStatus {
+    pub fn is_drifted(&self) -> bool {
+        self.state == EntryState::Drifted
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self.state, EntryState::Unknown { .. })
+    }
+}
+
+/// The status of every entry in a configuration
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusReport {
+    pub entries: Vec<EntryStatus>,
+}
+
+impl StatusReport {
+    /// Entries whose handler no longer matches the config
+    pub fn drifted(&self) -> impl Iterator<Item = &EntryStatus> {
+        self.entries.iter().filter(|entry| entry.is_drifted())
+    }
+
+    /// Entries that could not be checked
+    pub fn unknown(&self) -> impl Iterator<Item = &EntryStatus> {
+        self.entries.iter().filter(|entry| entry.is_unknown())
+    }
+
+    pub fn has_drift(&self) -> bool {
+        self.drifted().next().is_some()
+    }
+
+    pub fn has_unknown(&self) -> bool {
+        self.unknown().next().is_some()
+    }
+
+    /// Whether every entry was checked and matches the system
+    pub fn is_clean(&self) -> bool {
+        !self.has_drift() && !self.has_unknown()
+    }
+}
+
+/// Compare every entry of a configuration with the current handlers
+#[cfg(target_os = "macos")]
+pub fn check_config(config: &Config) -> Result<StatusReport> {
+    config.validate()?;
+
+    let entries = config
+        .entries()
+        .into_iter()
+        .map(|entry| {
+            let target = match entry.kind {
+                EntryKind::Type => association::type_target(&entry.key, config),
+                EntryKind::Mime => association::mime_target(&entry.key, config),
+                EntryKind::Extension => association::extension_target(&entry.key, config),
+                EntryKind::File => association::file_target(&entry.key, config),
+                EntryKind::Scheme => Ok(association::scheme_target(&entry.key)),
+            };
+            check_entry(&entry, target, &config.settings)
+        })
+        .collect();
+
+    Ok(StatusReport { entries })
+}
+
+#[cfg(target_os = "macos")]
+fn check_entry(
+    entry: &ConfigEntry,
+    target: Result<HandlerTarget>,
+    settings: &Settings,
+) -> EntryStatus {
+    let app_name = &entry.binding.app;
+    debug!("Checking {} → {}", entry, app_name);
+
+    let mut status = EntryStatus {
+        kind: entry.kind,
+        key: entry.key.to_string(),
+        supertype: entry.supertype.map(str::to_string),
+        target: None,
+        expected_app: app_name.to_string(),
+        expected_bundle_id: None,
+        actual_app: None,
+        actual_bundle_id: None,
+        state: EntryState::Matches,
+    };
+
+    let unknown = |status: EntryStatus, reason: String| EntryStatus {
+        state: EntryState::Unknown { reason },
+        ..status
+    };
+
+    let target = match target {
+        Ok(target) => target,
+        Err(e) => return unknown(status, e.to_string()),
+    };
+    status.target = Some(target.to_string());
+
+    let expected = match workspace::resolve_to_bundle_id(app_name, settings) {
+        Ok(bundle_id) => bundle_id,
+        Err(e) => return unknown(status, e.to_string()),
+    };
+    status.expected_bundle_id = Some(expected.clone());
+
+    let actual = match target.current_handler() {
+        Ok(actual) => actual,
+        Err(e) => return unknown(status, e.to_string()),
+    };
+
+    status.actual_app = actual.as_deref().map(|bundle_id| {
+        workspace::get_app_name_from_bundle_id(bundle_id, settings)
+            .unwrap_or_else(|_| bundle_id.to_string())
+    });
+
+    // Launch Services compares bundle identifiers case-insensitively
+    if !actual
+        .as_deref()
+        .is_some_and(|actual| actual.eq_ignore_ascii_case(&expected))
+    {
+        status.state = EntryState::Drifted;
+    }
+    status.actual_bundle_id = actual;
+
+    status
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(key: &str, state: EntryState) -> EntryStatus {
+        EntryStatus {
+            kind: EntryKind::Extension,
+            key: key.to_string(),
+            supertype: None,
+            target: None,
+            expected_app: "Missing".to_string(),
+            expected_bundle_id: None,
+            actual_app: None,
+            actual_bundle_id: None,
+            state,
+        }
+    }
+
+    #[test]
+    fn unknown_entries_are_not_clean() {
+        let unknown = |key| {
+            entry(
+                key,
+                EntryState::Unknown {
+                    reason: "Application 'Missing' not found".to_string(),
+                },
+            )
+        };
+        let report = StatusReport {
+            entries: vec![unknown("txt"), unknown("md")],
+        };
+
+        assert!(!report.has_drift());
+        assert!(report.has_unknown());
+        assert_eq!(report.unknown().count(), 2);
+        assert!(!report.is_clean());
+
+        let report = StatusReport {
+            entries: vec![entry("txt", EntryState::Matches)],
+        };
+        assert!(report.is_clean());
+    }
+}
@@ -0,0 +1,185 @@
+//! "Did you mean" suggestions for misspelled names
+//!
+//! Candidates are compared case-insensitively with `_` and `-` treated alike,
+//! the same way supertype names are parsed, and ranked by edit distance.
+
+use crate::{
+    app_index::{self, AppIndex},
+    discovery::SearchPath,
+    uti::{SuperType, UtiGraph},
+};
+use serde::Serialize;
+
+/// Most suggestions shown for a single name
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The known names closest to something that wasn't recognised
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct Suggestions(Vec<String>);
+
+impl Suggestions {
+    /// Rank candidates by edit distance, keeping the few close enough to be a typo
+    pub fn closest<I, S>(input: &str, candidates: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let wanted = normalize(input);
+        if wanted.is_empty() {
+            return Self::default();
+        }
+
+        // Allow roughly one edit per three characters
+        let max_distance = (wanted.chars().count() / 3).max(1);
+
+        let mut ranked: Vec<(usize, String)> = Vec::new();
+        for candidate in candidates {
+            let candidate = candidate.as_ref();
+            let normalized = normalize(candidate);
+
+            // An exact match isn't a suggestion, and neither is a duplicate
+            if normalized == wanted
+                || ranked
+                    .iter()
+                    .any(|(_, known)| known.eq_ignore_ascii_case(candidate))
+            {
+                continue;
+            }
+
+            let distance = strsim::damerau_levenshtein(&wanted, &normalized);
+            if distance <= max_distance {
+                ranked.push((distance, candidate.to_string()));
+            }
+        }
+
+        ranked.sort();
+        Self(
+            ranked
+                .into_iter()
+                .take(MAX_SUGGESTIONS)
+                .map(|(_, name)| name)
+                .collect(),
+        )
+    }
+
+    /// Supertype names and aliases close to the input
+    pub fn supertypes(input: &str) -> Self {
+        let names = SuperType::catalogue()
+            .iter()
+            .flat_map(|info| std::iter::once(info.name).chain(info.aliases.iter().copied()));
+        Self::closest(input, names)
+    }
+
+    /// Supertypes and declared UTIs close to the input
+    pub fn types(input: &str, graph: &UtiGraph) -> Self {
+        let supertypes = SuperType::catalogue().iter().map(|info| info.name);
+        let utis = graph
+            .iter()
+            .map(|declaration| declaration.identifier.as_str());
+        Self::closest(input, supertypes.chain(utis))
+    }
+
+    /// App names, display names and bundle IDs close to the input
+    pub fn apps(input: &str, index: &AppIndex) -> Self {
+        let names = index.apps().iter().flat_map(|app| {
+            [
+                Some(app.name.as_str()),
+                app.display_name.as_deref(),
+                app.bundle_name.as_deref(),
+                app.bundle_id.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+        });
+        Self::closest(input, names)
+    }
+
+    /// Like [`Suggestions::apps`], against the applications in the search paths
+    pub fn installed_apps(input: &str, roots: &[SearchPath]) -> Self {
+        app_index::shared(roots)
+            .map(|index| Self::apps(input, &index))
+            .unwrap_or_default()
+    }
+
+    /// Like [`Suggestions::types`], against the system and app-declared types
+    pub fn installed_types(input: &str, roots: &[SearchPath]) -> Self {
+        UtiGraph::installed(roots)
+            .map(|graph| Self::types(input, &graph))
+            .unwrap_or_default()
+    }
+
+    /// URL schemes declared by installed apps close to the input
+    pub fn schemes(input: &str, index: &AppIndex) -> Self {
+        let schemes = index.apps().iter().flat_map(|app| app.url_schemes.iter());
+        Self::closest(input, schemes)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+}
+
+/// Renders as ` (did you mean 'a', 'b' or 'c'?)`, or nothing at all, so it can
+/// trail an error message
+impl std::fmt::Display for Suggestions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Some((last, rest)) = self.0.split_last() else {
+            return Ok(());
+        };
+
+        write!(f, " (did you mean ")?;
+        for (i, name) in rest.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "'{name}'")?;
+        }
+        if !rest.is_empty() {
+            write!(f, " or ")?;
+        }
+        write!(f, "'{last}'?)")
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggests_close_names() {
+        assert_eq!(
+            Suggestions::supertypes("plain_txt").iter().next(),
+            Some("plain-text")
+        );
+
+        let apps = ["Safari", "Visual Studio Code", "com.apple.Safari"];
+        assert_eq!(
+            Suggestions::closest("safary", apps)
+                .iter()
+                .collect::<Vec<_>>(),
+            ["Safari"]
+        );
+        assert_eq!(
+            Suggestions::closest("com.aple.safari", apps).to_string(),
+            " (did you mean 'com.apple.Safari'?)"
+        );
+        assert_eq!(
+            Suggestions::closest("cat", ["dog", "car", "bat", "cap", "cow"]).to_string(),
+            " (did you mean 'bat', 'cap' or 'car'?)"
+        );
+
+        // Exact matches and unrelated names are not suggestions
+        assert!(Suggestions::closest("Safari", apps).is_empty());
+        assert!(Suggestions::closest("Xcode", apps).is_empty());
+        assert_eq!(Suggestions::default().to_string(), "");
+    }
+}
@@ -1,381 +1,313 @@
-use crate::error::{InfatError, Result};
+use crate::{
+    error::{InfatError, Result},
+    suggest::Suggestions,
+};
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
-/// Standard UTI supertypes that infat recognizes
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-pub enum SuperType {
+mod dynamic;
+mod graph;
+mod system;
+mod tags;
+
+pub use dynamic::DynamicUti;
+pub use graph::{declarations_in, DeclaringApp, TypeDeclaration, Uti, UtiGraph};
+pub use tags::{TagClass, TypeTags};
+
+/// One row of the supertype catalogue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperTypeInfo {
+    pub supertype: SuperType,
+    /// Canonical name, used for display and accepted by `FromStr`
+    pub name: &'static str,
+    /// Other names accepted by `FromStr`
+    pub aliases: &'static [&'static str],
+    pub uti: &'static str,
+    pub category: Category,
+}
+
+impl SuperTypeInfo {
+    /// Whether the name, an alias or the UTI contains the query, ignoring case
+    pub fn matches(&self, query: &str) -> bool {
+        let query = normalize(query);
+        std::iter::once(self.name)
+            .chain(self.aliases.iter().copied())
+            .chain(std::iter::once(self.uti))
+            .any(|name| normalize(name).contains(&query))
+    }
+
+    /// Up to `limit` extensions of this type, falling back to the types that
+    /// conform to it when the UTI is abstract (like `public.image`)
+    pub fn example_extensions(&self, limit: usize) -> Vec<&'static str> {
+        let graph = UtiGraph::system();
+        let mut extensions: Vec<&'static str> = Vec::new();
+
+        for identifier in std::iter::once(self.uti).chain(graph.descendants(self.uti)) {
+            for extension in graph
+                .get(identifier)
+                .into_iter()
+                .flat_map(|d| &d.extensions)
+            {
+                if extensions.len() == limit {
+                    return extensions;
+                }
+                if !extensions.contains(&extension.as_str()) {
+                    extensions.push(extension);
+                }
+            }
+        }
+
+        extensions
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace('_', "-")
+}
+
+macro_rules! categories {
+    ($($variant:ident => $name:literal, $label:literal;)*) => {
+        /// The groups supertypes are listed under
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum Category {
+            $($variant),*
+        }
+
+        impl Category {
+            pub const ALL: &'static [Category] = &[$(Self::$variant),*];
+
+            /// Short name, e.g. `source-code`
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name),*
+                }
+            }
+
+            /// Heading for listings, e.g. `Source Code`
+            pub fn label(&self) -> &'static str {
+                match self {
+                    $(Self::$variant => $label),*
+                }
+            }
+        }
+    };
+}
+
+/// Defines `SuperType` and its catalogue from one table.
+///
+/// Each row is `Variant => "name" ["alias", ...], "uti", Category;`, and the
+/// catalogue lists the rows in declaration order so a variant's discriminant is
+/// its index.
+macro_rules! supertypes {
+    ($($variant:ident => $name:literal $([$($alias:literal),*])?, $uti:literal, $category:ident;)*) => {
+        /// Standard UTI supertypes that infat recognizes
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum SuperType {
+            $($variant),*
+        }
+
+        static CATALOGUE: &[SuperTypeInfo] = &[$(SuperTypeInfo {
+            supertype: SuperType::$variant,
+            name: $name,
+            aliases: &[$($($alias),*)?],
+            uti: $uti,
+            category: Category::$category,
+        }),*];
+    };
+}
+
+categories! {
+    Text => "text", "Text & Documents";
+    Image => "image", "Images";
+    Audio => "audio", "Audio";
+    Video => "video", "Video";
+    Archive => "archive", "Archives";
+    SourceCode => "source-code", "Source Code";
+    System => "system", "System";
+    ThreeD => "3d", "3D Content";
+    Font => "font", "Fonts";
+    Cryptographic => "cryptographic", "Cryptographic";
+    Url => "url", "URLs";
+    PropertyList => "property-list", "Property Lists";
+    Misc => "misc", "Misc";
+    DefaultApp => "default-app", "Default apps";
+}
+
+supertypes! {
     // Text & Documents
-    Text,
-    PlainText,
-    Csv,
-    Json,
-    Xml,
-    Yaml,
-    Html,
-    Markdown,
-    Rtf,
+    Text => "text", "public.text", Text;
+    PlainText => "plain-text", "public.plain-text", Text;
+    Csv => "csv" ["comma-separated-text"], "public.comma-separated-values-text", Text;
+    Json => "json", "public.json", Text;
+    Xml => "xml", "public.xml", Text;
+    Yaml => "yaml", "public.yaml", Text;
+    Html => "html", "public.html", Text;
+    Markdown => "markdown" ["md"], "net.daringfireball.markdown", Text;
+    Rtf => "rtf", "public.rtf", Text;
 
     // Images
-    Image,
-    RawImage,
-    Png,
-    Jpeg,
-    Gif,
-    Tiff,
-    Svg,
-    WebP,
-    Heic,
-    Heif,
-    Bmp,
+    Image => "image", "public.image", Image;
+    RawImage => "raw-image", "public.camera-raw-image", Image;
+    Png => "png", "public.png", Image;
+    Jpeg => "jpeg", "public.jpeg", Image;
+    Gif => "gif", "com.compuserve.gif", Image;
+    Tiff => "tiff", "public.tiff", Image;
+    Svg => "svg", "public.svg-image", Image;
+    WebP => "webp", "org.webmproject.webp", Image;
+    Heic => "heic", "public.heic", Image;
+    Heif => "heif", "public.heif", Image;
+    Bmp => "bmp", "com.microsoft.bmp", Image;
 
     // Audio
-    Audio,
-    Mp3,
-    Wav,
-    Aiff,
-    Midi,
-    Mp4Audio,
-    AppleProtectedMp4Audio,
-    Flac,
-    OggAudio,
-    Ac3Audio,
-    AacAudio,
+    Audio => "audio", "public.audio", Audio;
+    Mp3 => "mp3", "public.mp3", Audio;
+    Wav => "wav", "com.microsoft.waveform-audio", Audio;
+    Aiff => "aiff", "public.aiff-audio", Audio;
+    Midi => "midi", "public.midi-audio", Audio;
+    Mp4Audio => "mp4-audio", "public.mpeg-4-audio", Audio;
+    M4aAudio => "m4a-audio", "com.apple.m4a-audio", Audio;
+    AppleProtectedMp4Audio => "apple-protected-mp4-audio", "com.apple.protected-mpeg-4-audio", Audio;
+    Flac => "flac", "org.xiph.flac", Audio;
+    OggAudio => "ogg-audio", "org.xiph.ogg-audio", Audio;
+    Ac3Audio => "ac3-audio", "public.ac3-audio", Audio;
+    AacAudio => "aac-audio", "public.aac-audio", Audio;
+    RealAudio => "real-audio", "com.real.realaudio", Audio;
 
     // Video
-    Video,
-    Mpeg2TransportStream,
-    Movie,
-    QuicktimeMovie,
-    Mp4Movie,
-    AppleProtectedMp4Video,
-    Mpeg,
-    Mpeg2Video,
-    Avi,
-    DvMovie,
-    RealMedia,
-    RealAudio,
-    Webm,
-    Matroska,
-    M3uPlaylist,
+    Video => "video", "public.video", Video;
+    Movie => "movie", "public.movie", Video;
+    QuicktimeMovie => "quicktime-movie" ["quicktime"], "com.apple.quicktime-movie", Video;
+    Mp4Movie => "mp4-movie" ["mpeg4-movie", "mp4"], "public.mpeg-4", Video;
+    M4vVideo => "m4v-video", "com.apple.m4v-video", Video;
+    AppleProtectedMp4Video => "apple-protected-mp4-video", "com.apple.protected-mpeg-4-video", Video;
+    Mpeg => "mpeg", "public.mpeg", Video;
+    Mpeg2Video => "mpeg2-video", "public.mpeg-2-video", Video;
+    Mpeg2TransportStream => "mpeg2-transport-stream", "public.mpeg-2-transport-stream", Video;
+    Avi => "avi", "public.avi", Video;
+    DvMovie => "dv-movie", "public.dv-movie", Video;
+    RealMedia => "real-media", "com.real.realmedia", Video;
+    Webm => "webm", "org.webmproject.webm", Video;
+    Matroska => "matroska", "org.matroska.mkv", Video;
+    M3uPlaylist => "m3u-playlist" ["m3u"], "public.m3u-playlist", Video;
 
     // Archives
-    Archive,
-    Zip,
-    Gzip,
-    Tar,
-    Bz2,
-    AppleArchive,
+    Archive => "archive", "public.archive", Archive;
+    Zip => "zip", "public.zip-archive", Archive;
+    Gzip => "gzip", "org.gnu.gnu-zip-archive", Archive;
+    Tar => "tar", "public.tar-archive", Archive;
+    Bz2 => "bz2", "public.bzip2-archive", Archive;
+    AppleArchive => "apple-archive", "com.apple.archive", Archive;
 
     // Source Code
-    Sourcecode,
-    TypeScript,
-    CSource,
-    CHeader,
-    CppSource,
-    CppHeader,
-    ObjcSource,
-    ObjcPlusPlusSource,
-    SwiftSource,
-    Shell,
-    Makefile,
-    Javascript,
-    PythonScript,
-    RubyScript,
-    PerlScript,
-    PhpScript,
-    AppleScript,
-    AssemblySource,
+    Sourcecode => "sourcecode" ["source-code"], "public.source-code", SourceCode;
+    TypeScript => "typescript" ["ts"], "com.microsoft.typescript", SourceCode;
+    CSource => "c-source", "public.c-source", SourceCode;
+    CHeader => "c-header", "public.c-header", SourceCode;
+    CppSource => "cpp-source", "public.c-plus-plus-source", SourceCode;
+    CppHeader => "cpp-header", "public.c-plus-plus-header", SourceCode;
+    ObjcSource => "objc-source", "public.objective-c-source", SourceCode;
+    ObjcPlusPlusSource => "objc-plus-plus-source", "public.objective-c-plus-plus-source", SourceCode;
+    SwiftSource => "swift-source", "public.swift-source", SourceCode;
+    Shell => "shell", "public.shell-script", SourceCode;
+    Makefile => "makefile", "public.make-source", SourceCode;
+    Javascript => "javascript", "com.netscape.javascript-source", SourceCode;
+    PythonScript => "python-script", "public.python-script", SourceCode;
+    RubyScript => "ruby-script", "public.ruby-script", SourceCode;
+    PerlScript => "perl-script", "public.perl-script", SourceCode;
+    PhpScript => "php-script", "public.php-script", SourceCode;
+    AppleScript => "apple-script", "com.apple.applescript.text", SourceCode;
+    AssemblySource => "assembly-source", "public.assembly-source", SourceCode;
 
     // System
-    Data,
-    Directory,
-    Folder,
-    Symlink,
-    Executable,
-    UnixExecutable,
-    AppBundle,
-    Framework,
-    DiskImage,
-    Volume,
-    MountPoint,
-    AliasFile,
+    Data => "data", "public.data", System;
+    Directory => "directory", "public.directory", System;
+    Folder => "folder", "public.folder", System;
+    Symlink => "symlink", "public.symlink", System;
+    Executable => "executable", "public.executable", System;
+    UnixExecutable => "unix-executable", "public.unix-executable", System;
+    AppBundle => "app-bundle", "com.apple.application-bundle", System;
+    Framework => "framework", "com.apple.framework", System;
+    DiskImage => "disk-image", "public.disk-image", System;
+    Volume => "volume", "public.volume", System;
+    MountPoint => "mount-point", "com.apple.mount-point", System;
+    AliasFile => "alias-file", "com.apple.alias-file", System;
 
     // 3D Content
-    ThreeDContent,
-    Usd,
-    Usdz,
-    RealityFile,
-    SceneKitScene,
+    ThreeDContent => "3d-content", "public.3d-content", ThreeD;
+    Usd => "usd", "com.pixar.universal-scene-description", ThreeD;
+    Usdz => "usdz", "com.pixar.universal-scene-description-mobile", ThreeD;
+    RealityFile => "reality-file", "com.apple.reality", ThreeD;
+    SceneKitScene => "scenekit-scene", "com.apple.scenekit.scene", ThreeD;
 
     // Fonts
-    Font,
+    Font => "font", "public.font", Font;
 
     // Cryptographic
-    Pkcs12,
-    X509Certificate,
+    Pkcs12 => "pkcs12", "com.rsa.pkcs-12", Cryptographic;
+    X509Certificate => "x509-certificate", "public.x509-certificate", Cryptographic;
 
     // URLs
-    Url,
-    FileUrl,
-    UrlBookmarkData,
+    Url => "url", "public.url", Url;
+    FileUrl => "file-url", "public.file-url", Url;
+    UrlBookmarkData => "url-bookmark-data", "com.apple.bookmark", Url;
+    Bookmark => "bookmark", "public.bookmark", Url;
+    InternetLocation => "internet-location", "com.apple.web-internet-location", Url;
+    InternetShortcut => "internet-shortcut", "com.microsoft.internet-shortcut", Url;
 
     // Property Lists
-    PropertyList,
-    XmlPropertyList,
-    BinaryPropertyList,
+    PropertyList => "property-list", "com.apple.property-list", PropertyList;
+    XmlPropertyList => "xml-property-list", "com.apple.xml-property-list", PropertyList;
+    BinaryPropertyList => "binary-property-list", "com.apple.binary-property-list", PropertyList;
 
     // Misc
-    Log,
-    Bookmark,
-    InternetLocation,
-    InternetShortcut,
-
-    // Apple-specific
-    DefaultAppWebBrowser,
-    DefaultAppMailClient,
-    M4vVideo,
-    M4aAudio,
+    Log => "log", "public.log", Misc;
+
+    // Default apps
+    DefaultAppWebBrowser => "default-app-web-browser", "com.apple.default-app.web-browser", DefaultApp;
+    DefaultAppMailClient => "default-app-mail-client", "com.apple.default-app.mail-client", DefaultApp;
 }
 
 impl SuperType {
+    /// The catalogue row for this supertype
+    pub fn info(&self) -> &'static SuperTypeInfo {
+        &CATALOGUE[*self as usize]
+    }
+
+    /// Canonical name, e.g. `plain-text`
+    pub fn name(&self) -> &'static str {
+        self.info().name
+    }
+
+    /// Other names this supertype can be written as
+    pub fn aliases(&self) -> &'static [&'static str] {
+        self.info().aliases
+    }
+
     /// Get the corresponding macOS UTI string
     pub fn uti_string(&self) -> &'static str {
-        match self {
-            Self::Text => "public.text",
-            Self::TypeScript => "com.microsoft.typescript",
-            Self::PlainText => "public.plain-text",
-            Self::Csv => "public.comma-separated-values-text",
-            Self::Json => "public.json",
-            Self::Xml => "public.xml",
-            Self::Yaml => "public.yaml",
-            Self::Html => "public.html",
-            Self::Markdown => "net.daringfireball.markdown",
-            Self::Rtf => "public.rtf",
-
-            Self::Image => "public.image",
-            Self::RawImage => "public.camera-raw-image",
-            Self::Png => "public.png",
-            Self::Jpeg => "public.jpeg",
-            Self::Gif => "com.compuserve.gif",
-            Self::Tiff => "public.tiff",
-            Self::Svg => "public.svg-image",
-            Self::WebP => "org.webmproject.webp",
-            Self::Heic => "public.heic",
-            Self::Heif => "public.heif",
-            Self::Bmp => "com.microsoft.bmp",
-
-            Self::Audio => "public.audio",
-            Self::Mp3 => "public.mp3",
-            Self::Wav => "com.microsoft.waveform-audio",
-            Self::Aiff => "public.aiff-audio",
-            Self::Midi => "public.midi-audio",
-            Self::Mp4Audio => "public.mpeg-4-audio",
-            Self::AppleProtectedMp4Audio => "com.apple.protected-mpeg-4-audio",
-            Self::Flac => "org.xiph.flac",
-            Self::OggAudio => "org.xiph.ogg-audio",
-            Self::Ac3Audio => "public.ac3-audio",
-            Self::AacAudio => "public.aac-audio",
-
-            Self::Video => "public.video",
-            Self::Movie => "public.movie",
-            Self::QuicktimeMovie => "com.apple.quicktime-movie",
-            Self::Mp4Movie => "public.mpeg-4",
-            Self::AppleProtectedMp4Video => "com.apple.protected-mpeg-4-video",
-            Self::Mpeg => "public.mpeg",
-            Self::Mpeg2Video => "public.mpeg-2-video",
-            Self::Avi => "public.avi",
-            Self::DvMovie => "public.dv-movie",
-            Self::RealMedia => "com.real.realmedia",
-            Self::RealAudio => "com.real.realaudio",
-            Self::Mpeg2TransportStream => "mpeg2-transport-stream",
-            Self::Webm => "org.webmproject.webm",
-            Self::M3uPlaylist => "m3u-playlist",
-            Self::Matroska => "org.matroska.mkv",
-
-            Self::Archive => "public.archive",
-            Self::Zip => "public.zip-archive",
-            Self::Gzip => "org.gnu.gnu-zip-archive",
-            Self::Tar => "public.tar-archive",
-            Self::Bz2 => "public.bzip2-archive",
-            Self::AppleArchive => "com.apple.archive",
-
-            Self::Sourcecode => "public.source-code",
-            Self::CSource => "public.c-source",
-            Self::CHeader => "public.c-header",
-            Self::CppSource => "public.c-plus-plus-source",
-            Self::CppHeader => "public.c-plus-plus-header",
-            Self::ObjcSource => "public.objective-c-source",
-            Self::ObjcPlusPlusSource => "public.objective-c-plus-plus-source",
-            Self::SwiftSource => "public.swift-source",
-            Self::Shell => "public.shell-script",
-            Self::Makefile => "public.make-source",
-            Self::Javascript => "com.netscape.javascript-source",
-            Self::PythonScript => "public.python-script",
-            Self::RubyScript => "public.ruby-script",
-            Self::PerlScript => "public.perl-script",
-            Self::PhpScript => "public.php-script",
-            Self::AppleScript => "com.apple.applescript.text",
-            Self::AssemblySource => "public.assembly-source",
-
-            Self::Data => "public.data",
-            Self::Directory => "public.directory",
-            Self::Folder => "public.folder",
-            Self::Symlink => "public.symlink",
-            Self::Executable => "public.executable",
-            Self::UnixExecutable => "public.unix-executable",
-            Self::AppBundle => "com.apple.application-bundle",
-            Self::Framework => "com.apple.framework",
-            Self::DiskImage => "public.disk-image",
-            Self::Volume => "public.volume",
-            Self::MountPoint => "com.apple.mount-point",
-            Self::AliasFile => "com.apple.alias-file",
-
-            Self::ThreeDContent => "public.3d-content",
-            Self::Usd => "com.pixar.universal-scene-description",
-            Self::Usdz => "com.pixar.universal-scene-description-mobile",
-            Self::RealityFile => "com.apple.reality",
-            Self::SceneKitScene => "com.apple.scenekit.scene",
-
-            Self::Font => "public.font",
-
-            Self::Pkcs12 => "com.rsa.pkcs-12",
-            Self::X509Certificate => "public.x509-certificate",
-
-            Self::Url => "public.url",
-            Self::FileUrl => "public.file-url",
-            Self::UrlBookmarkData => "com.apple.bookmark",
-
-            Self::PropertyList => "com.apple.property-list",
-            Self::XmlPropertyList => "com.apple.xml-property-list",
-            Self::BinaryPropertyList => "com.apple.binary-property-list",
-
-            Self::Log => "public.log",
-            Self::Bookmark => "public.bookmark",
-            Self::InternetLocation => "com.apple.web-internet-location",
-            Self::InternetShortcut => "com.microsoft.internet-shortcut",
-
-            Self::DefaultAppWebBrowser => "com.apple.default-app.web-browser",
-            Self::DefaultAppMailClient => "com.apple.default-app.mail-client",
-            Self::M4vVideo => "com.apple.m4v-video",
-            Self::M4aAudio => "com.apple.m4a-audio",
-        }
+        self.info().uti
+    }
+
+    pub fn category(&self) -> Category {
+        self.info().category
+    }
+
+    /// Every supertype with its names, UTI and category
+    pub fn catalogue() -> &'static [SuperTypeInfo] {
+        CATALOGUE
     }
 
     /// Get all available supertypes
     pub fn all() -> Vec<SuperType> {
-        vec![
-            Self::Text,
-            Self::TypeScript,
-            Self::PlainText,
-            Self::Csv,
-            Self::Json,
-            Self::Xml,
-            Self::Yaml,
-            Self::M3uPlaylist,
-            Self::Html,
-            Self::Markdown,
-            Self::Rtf,
-            Self::Image,
-            Self::RawImage,
-            Self::Png,
-            Self::Jpeg,
-            Self::Gif,
-            Self::Tiff,
-            Self::Svg,
-            Self::WebP,
-            Self::Heic,
-            Self::Heif,
-            Self::Bmp,
-            Self::Audio,
-            Self::Mp3,
-            Self::Wav,
-            Self::Aiff,
-            Self::Midi,
-            Self::Mp4Audio,
-            Self::AppleProtectedMp4Audio,
-            Self::Flac,
-            Self::OggAudio,
-            Self::Ac3Audio,
-            Self::AacAudio,
-            Self::Video,
-            Self::Movie,
-            Self::QuicktimeMovie,
-            Self::Mp4Movie,
-            Self::AppleProtectedMp4Video,
-            Self::Mpeg,
-            Self::Mpeg2Video,
-            Self::Avi,
-            Self::DvMovie,
-            Self::RealMedia,
-            Self::RealAudio,
-            Self::Webm,
-            Self::Matroska,
-            Self::Archive,
-            Self::Zip,
-            Self::Gzip,
-            Self::Tar,
-            Self::Bz2,
-            Self::AppleArchive,
-            Self::Sourcecode,
-            Self::CSource,
-            Self::CHeader,
-            Self::CppSource,
-            Self::CppHeader,
-            Self::ObjcSource,
-            Self::ObjcPlusPlusSource,
-            Self::SwiftSource,
-            Self::Shell,
-            Self::Makefile,
-            Self::Javascript,
-            Self::PythonScript,
-            Self::RubyScript,
-            Self::PerlScript,
-            Self::PhpScript,
-            Self::AppleScript,
-            Self::AssemblySource,
-            Self::Data,
-            Self::Directory,
-            Self::Folder,
-            Self::Symlink,
-            Self::Executable,
-            Self::UnixExecutable,
-            Self::AppBundle,
-            Self::Framework,
-            Self::DiskImage,
-            Self::Volume,
-            Self::MountPoint,
-            Self::AliasFile,
-            Self::ThreeDContent,
-            Self::Usd,
-            Self::Usdz,
-            Self::RealityFile,
-            Self::SceneKitScene,
-            Self::Font,
-            Self::Pkcs12,
-            Self::X509Certificate,
-            Self::Url,
-            Self::FileUrl,
-            Self::UrlBookmarkData,
-            Self::PropertyList,
-            Self::Mpeg2TransportStream,
-            Self::XmlPropertyList,
-            Self::BinaryPropertyList,
-            Self::Log,
-            Self::Bookmark,
-            Self::InternetLocation,
-            Self::InternetShortcut,
-            Self::DefaultAppWebBrowser,
-            Self::DefaultAppMailClient,
-            Self::M4vVideo,
-            Self::M4aAudio,
-        ]
+        CATALOGUE.iter().map(|info| info.supertype).collect()
     }
 
     /// Try to find a supertype by UTI string
     pub fn from_uti_string(uti: &str) -> Option<SuperType> {
-        Self::all().into_iter().find(|st| st.uti_string() == uti)
+        CATALOGUE
+            .iter()
+            .find(|info| info.uti.eq_ignore_ascii_case(uti))
+            .map(|info| info.supertype)
     }
 }
 
@@ -383,134 +315,185 @@ impl FromStr for SuperType {
     type Err = InfatError;
 
     fn from_str(s: &str) -> Result<Self> {
-        let normalized = s.to_lowercase().replace('_', "-");
-
-        match normalized.as_str() {
-            "text" => Ok(Self::Text),
-            "plain-text" => Ok(Self::PlainText),
-            "json" => Ok(Self::Json),
-            "xml" => Ok(Self::Xml),
-            "yaml" => Ok(Self::Yaml),
-            "html" => Ok(Self::Html),
-            "markdown" | "md" => Ok(Self::Markdown),
-            "rtf" => Ok(Self::Rtf),
-
-            "image" => Ok(Self::Image),
-            "raw-image" => Ok(Self::RawImage),
-            "png" => Ok(Self::Png),
-            "jpeg" => Ok(Self::Jpeg),
-            "gif" => Ok(Self::Gif),
-            "tiff" => Ok(Self::Tiff),
-            "svg" => Ok(Self::Svg),
-            "webp" => Ok(Self::WebP),
-            "heic" => Ok(Self::Heic),
-            "heif" => Ok(Self::Heif),
-            "bmp" => Ok(Self::Bmp),
-
-            "audio" => Ok(Self::Audio),
-            "mp3" => Ok(Self::Mp3),
-            "wav" => Ok(Self::Wav),
-            "aiff" => Ok(Self::Aiff),
-            "midi" => Ok(Self::Midi),
-            "mp4-audio" => Ok(Self::Mp4Audio),
-
-            "video" => Ok(Self::Video),
-            "movie" => Ok(Self::Movie),
-            "quicktime-movie" => Ok(Self::QuicktimeMovie),
-            "mp4-movie" => Ok(Self::Mp4Movie),
-            "mpeg" => Ok(Self::Mpeg),
-            "avi" => Ok(Self::Avi),
-
-            "csv" | "comma-separated-text" => Ok(Self::Csv),
-            "mpeg2-video" => Ok(Self::Mpeg2Video),
-            "mpeg2-transport-stream" => Ok(Self::Mpeg2TransportStream),
-            "typescript" | "ts" => Ok(Self::TypeScript),
-            "mpeg4-movie" | "mp4" => Ok(Self::Mp4Movie),
-            "m3u" | "m3u-playlist" => Ok(Self::M3uPlaylist),
-
-            "archive" => Ok(Self::Archive),
-            "zip" => Ok(Self::Zip),
-            "gzip" => Ok(Self::Gzip),
-            "tar" => Ok(Self::Tar),
-
-            "sourcecode" => Ok(Self::Sourcecode),
-            "c-source" => Ok(Self::CSource),
-            "cpp-source" => Ok(Self::CppSource),
-            "objc-source" => Ok(Self::ObjcSource),
-            "swift-source" => Ok(Self::SwiftSource),
-            "shell" => Ok(Self::Shell),
-            "makefile" => Ok(Self::Makefile),
-            "javascript" => Ok(Self::Javascript),
-            "python-script" => Ok(Self::PythonScript),
-
-            "data" => Ok(Self::Data),
-            "directory" => Ok(Self::Directory),
-            "folder" => Ok(Self::Folder),
-            "symlink" => Ok(Self::Symlink),
-            "executable" => Ok(Self::Executable),
-            "unix-executable" => Ok(Self::UnixExecutable),
-            "app-bundle" => Ok(Self::AppBundle),
-
-            _ => Err(InfatError::UnsupportedSupertype {
+        let normalized = normalize(s);
+
+        CATALOGUE
+            .iter()
+            .find(|info| info.name == normalized || info.aliases.contains(&normalized.as_str()))
+            .map(|info| info.supertype)
+            .ok_or_else(|| InfatError::UnsupportedSupertype {
                 name: s.to_string(),
-            }),
-        }
+                suggestions: Suggestions::supertypes(s),
+            })
     }
 }
 
 impl std::fmt::Display for SuperType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let kebab_case = match self {
-            Self::PlainText => "plain-text",
-            Self::RawImage => "raw-image",
-            Self::Mp4Audio => "mp4-audio",
-            Self::AppleProtectedMp4Audio => "apple-protected-mp4-audio",
-            Self::QuicktimeMovie => "quicktime-movie",
-            Self::Mp4Movie => "mp4-movie",
-            Self::AppleProtectedMp4Video => "apple-protected-mp4-video",
-            Self::Mpeg2Video => "mpeg2-video",
-            Self::DvMovie => "dv-movie",
-            Self::AppleArchive => "apple-archive",
-            Self::CSource => "c-source",
-            Self::CHeader => "c-header",
-            Self::CppSource => "cpp-source",
-            Self::CppHeader => "cpp-header",
-            Self::ObjcSource => "objc-source",
-            Self::ObjcPlusPlusSource => "objc-plus-plus-source",
-            Self::SwiftSource => "swift-source",
-            Self::PythonScript => "python-script",
-            Self::RubyScript => "ruby-script",
-            Self::PerlScript => "perl-script",
-            Self::PhpScript => "php-script",
-            Self::AppleScript => "apple-script",
-            Self::AssemblySource => "assembly-source",
-            Self::UnixExecutable => "unix-executable",
-            Self::AppBundle => "app-bundle",
-            Self::DiskImage => "disk-image",
-            Self::MountPoint => "mount-point",
-            Self::AliasFile => "alias-file",
-            Self::ThreeDContent => "3d-content",
-            Self::RealityFile => "reality-file",
-            Self::SceneKitScene => "scenekit-scene",
-            Self::X509Certificate => "x509-certificate",
-            Self::FileUrl => "file-url",
-            Self::UrlBookmarkData => "url-bookmark-data",
-            Self::PropertyList => "property-list",
-            Self::XmlPropertyList => "xml-property-list",
-            Self::BinaryPropertyList => "binary-property-list",
-            Self::InternetLocation => "internet-location",
-            Self::InternetShortcut => "internet-shortcut",
-            Self::DefaultAppWebBrowser => "default-app-web-browser",
-            Self::DefaultAppMailClient => "default-app-mail-client",
-            Self::M4vVideo => "m4v-video",
-            Self::M4aAudio => "m4a-audio",
-            _ => {
-                return write!(f, "{self:?}")
-                    .map(|_| ())
-                    .map_err(|_| std::fmt::Error)
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Category {
+    type Err = InfatError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = normalize(s);
+
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|category| category.name() == normalized)
+            .ok_or_else(|| InfatError::UnknownCategory {
+                name: s.to_string(),
+                suggestions: Suggestions::closest(s, Self::ALL.iter().map(|c| c.name())),
+            })
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Serialized by name so config files and JSON use the same spelling as the CLI
+impl Serialize for SuperType {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl Serialize for Category {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for SuperType {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use proptest::sample::select;
+    use std::collections::HashSet;
+
+    /// Rewrite a name the way users might type it: any case, `_` for `-`
+    fn respell(name: &str, upper: &[bool], underscores: bool) -> String {
+        name.chars()
+            .zip(upper.iter().cycle())
+            .map(|(c, &upper)| match c {
+                '-' if underscores => '_',
+                c if upper => c.to_ascii_uppercase(),
+                c => c,
+            })
+            .collect()
+    }
+
+    proptest! {
+        #[test]
+        fn parse_display_round_trips(
+            supertype in select(SuperType::all()),
+            upper in prop::collection::vec(any::<bool>(), 1..8),
+            underscores in any::<bool>(),
+        ) {
+            prop_assert_eq!(supertype.to_string().parse::<SuperType>()?, supertype);
+
+            let respelled = respell(&supertype.to_string(), &upper, underscores);
+            prop_assert_eq!(respelled.parse::<SuperType>()?, supertype);
+
+            for alias in supertype.aliases() {
+                prop_assert_eq!(alias.parse::<SuperType>()?, supertype);
+            }
+        }
+
+        #[test]
+        fn uti_string_round_trips(
+            supertype in select(SuperType::all()),
+            upper in prop::collection::vec(any::<bool>(), 1..8),
+        ) {
+            prop_assert_eq!(SuperType::from_uti_string(supertype.uti_string()), Some(supertype));
+
+            let respelled = respell(supertype.uti_string(), &upper, false);
+            prop_assert_eq!(SuperType::from_uti_string(&respelled), Some(supertype));
+        }
+
+        #[test]
+        fn serde_uses_display_names(supertype in select(SuperType::all())) {
+            let value = toml::Value::try_from(supertype).unwrap();
+            prop_assert_eq!(value.as_str(), Some(supertype.name()));
+            prop_assert_eq!(value.try_into::<SuperType>().unwrap(), supertype);
+        }
+    }
+
+    #[test]
+    fn browses_the_catalogue() {
+        for category in Category::ALL {
+            assert_eq!(category.name().parse::<Category>().unwrap(), *category);
+            assert_eq!(
+                category.name().to_uppercase().parse::<Category>().unwrap(),
+                *category
+            );
+        }
+        assert!(matches!(
+            "imgae".parse::<Category>(),
+            Err(InfatError::UnknownCategory { suggestions, .. })
+                if suggestions.iter().next() == Some("image")
+        ));
+
+        let markdown = SuperType::Markdown.info();
+        assert!(markdown.matches("MD"));
+        assert!(markdown.matches("daringfireball"));
+        assert!(!markdown.matches("html"));
+
+        // Abstract types borrow extensions from the types conforming to them
+        assert!(SuperType::Jpeg
+            .info()
+            .example_extensions(5)
+            .contains(&"jpg"));
+        let images = SuperType::Image.info().example_extensions(5);
+        assert_eq!(images.len(), 5);
+        assert!(SuperType::DefaultAppWebBrowser
+            .info()
+            .example_extensions(5)
+            .is_empty());
+    }
+
+    #[test]
+    fn names_and_utis_are_unique() {
+        let mut names = HashSet::new();
+        let mut utis = HashSet::new();
+
+        for (index, info) in SuperType::catalogue().iter().enumerate() {
+            assert_eq!(info.supertype as usize, index);
+            assert!(
+                utis.insert(info.uti.to_lowercase()),
+                "duplicate UTI {}",
+                info.uti
+            );
+
+            for name in std::iter::once(&info.name).chain(info.aliases) {
+                assert_eq!(*name, name.to_lowercase().replace('_', "-"));
+                assert!(names.insert(*name), "duplicate name {name}");
             }
-        };
+        }
 
-        write!(f, "{}", kebab_case.to_lowercase())
+        assert!(Category::ALL
+            .iter()
+            .all(|category| SuperType::all().iter().any(|st| st.category() == *category)));
     }
 }
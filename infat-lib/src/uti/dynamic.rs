@@ -0,0 +1,315 @@
+//! Dynamic type identifiers (`dyn.*`)
+//!
+//! When nothing declares a type for a tag, Launch Services makes one up by
+//! encoding the tag itself: `dyn.a` followed by a base-32 rendering of a
+//! payload like `?0=6:1=rmd`, which reads "conforms to `public.data`, filename
+//! extension `rmd`". Keys and common values in the payload are abbreviated to
+//! single characters.
+
+use super::TagClass;
+use std::fmt::Write as _;
+
+/// Prefix of every dynamic identifier, including the encoding version
+const PREFIX: &str = "dyn.a";
+
+/// Base-32 alphabet; `i`, `j`, `l` and `o` are left out to avoid lookalikes
+const ALPHABET: &[u8; 32] = b"abcdefghkmnpqrstuvwxyz0123456789";
+
+/// Abbreviations used for keys and values in the payload
+const ABBREVIATIONS: &[(char, &str)] = &[
+    ('0', "UTTypeConformsTo"),
+    ('1', "public.filename-extension"),
+    ('2', "com.apple.ostype"),
+    ('3', "public.mime-type"),
+    ('4', "com.apple.nspboard-type"),
+    ('5', "public.url-scheme"),
+    ('6', "public.data"),
+    ('7', "public.text"),
+    ('8', "public.plain-text"),
+    ('9', "public.utf16-plain-text"),
+    ('A', "com.apple.traditional-mac-plain-text"),
+    ('B', "public.image"),
+    ('C', "public.video"),
+    ('D', "public.audio"),
+    ('E', "public.directory"),
+    ('F', "public.folder"),
+];
+
+const CONFORMS_TO: &str = "UTTypeConformsTo";
+
+/// The contents of a decoded `dyn.*` identifier
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DynamicUti {
+    /// Types the dynamic type conforms to, e.g. `public.data`
+    pub conforms_to: Vec<String>,
+
+    /// Tag class identifiers and tags, e.g. `("public.filename-extension", "rmd")`
+    pub tags: Vec<(String, String)>,
+}
+
+impl DynamicUti {
+    /// A dynamic type for a single tag
+    pub fn for_tag(tag_class: &str, tag: &str, conforms_to: &str) -> Self {
+        Self {
+            conforms_to: vec![conforms_to.to_string()],
+            tags: vec![(tag_class.to_string(), tag.to_string())],
+        }
+    }
+
+    /// Whether an identifier is a dynamic one
+    pub fn is_dynamic(identifier: &str) -> bool {
+        identifier.starts_with("dyn.")
+    }
+
+    /// Decode a `dyn.*` identifier, or `None` if it isn't one or is malformed
+    pub fn decode(identifier: &str) -> Option<Self> {
+        let payload = identifier.strip_prefix(PREFIX)?;
+        let payload = String::from_utf8(decode_base32(payload)?).ok()?;
+        let payload = payload.strip_prefix('?')?;
+
+        let mut decoded = Self::default();
+        for (key, value) in parse_payload(payload)? {
+            if key == CONFORMS_TO {
+                decoded.conforms_to.push(value);
+            } else {
+                decoded.tags.push((key, value));
+            }
+        }
+
+        Some(decoded)
+    }
+
+    /// Encode as a `dyn.*` identifier, the way Launch Services would
+    pub fn encode(&self) -> String {
+        let pairs = self
+            .conforms_to
+            .iter()
+            .map(|parent| (CONFORMS_TO, parent.as_str()))
+            .chain(
+                self.tags
+                    .iter()
+                    .map(|(class, tag)| (class.as_str(), tag.as_str())),
+            );
+
+        let mut payload = String::from("?");
+        for (i, (key, value)) in pairs.enumerate() {
+            if i > 0 {
+                payload.push(':');
+            }
+            let _ = write!(payload, "{}={}", abbreviate(key), abbreviate(value));
+        }
+
+        format!("{PREFIX}{}", encode_base32(payload.as_bytes()))
+    }
+
+    /// The first tag of the given class, e.g. the extension for
+    /// `public.filename-extension`
+    pub fn tag(&self, tag_class: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(class, _)| class == tag_class)
+            .map(|(_, tag)| tag.as_str())
+    }
+
+    /// The tag a handler for this type is configured by: its extension, or
+    /// else its MIME type. A type known only by other tags, such as an
+    /// OSType, has none and keeps its `dyn.*` identifier.
+    pub fn handler_tag(&self) -> Option<(TagClass, &str)> {
+        [TagClass::Extension, TagClass::MimeType]
+            .into_iter()
+            .find_map(|class| self.tag(class.identifier()).map(|tag| (class, tag)))
+    }
+}
+
+/// Renders as `public.filename-extension = rmd (conforms to public.data)`
+impl std::fmt::Display for DynamicUti {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (class, tag)) in self.tags.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{class} = {tag}")?;
+        }
+
+        if !self.conforms_to.is_empty() {
+            if !self.tags.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "(conforms to {})", self.conforms_to.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Split the payload into key/value pairs, expanding abbreviations that
+/// weren't escaped with `\`
+fn parse_payload(payload: &str) -> Option<Vec<(String, String)>> {
+    let mut pairs = Vec::new();
+    let mut key = None;
+    let mut current = String::new();
+    let mut escaped = false;
+    let mut chars = payload.chars();
+
+    loop {
+        let next = chars.next();
+        match next {
+            Some('\\') => {
+                current.push(chars.next()?);
+                escaped = true;
+            }
+            Some('=') if key.is_none() => key = Some(finish(&mut current, &mut escaped)),
+            Some(':') | None => {
+                let value = finish(&mut current, &mut escaped);
+                pairs.push((key.take()?, value));
+                if next.is_none() {
+                    return Some(pairs);
+                }
+            }
+            Some(c) => current.push(c),
+        }
+    }
+}
+
+/// Take the text collected so far, expanding it unless it was escaped
+fn finish(current: &mut String, escaped: &mut bool) -> String {
+    let text = std::mem::take(current);
+    if std::mem::take(escaped) {
+        text
+    } else {
+        expand(text)
+    }
+}
+
+fn expand(text: String) -> String {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(code), None) => ABBREVIATIONS
+            .iter()
+            .find(|(abbreviation, _)| *abbreviation == code)
+            .map_or(text, |(_, full)| full.to_string()),
+        _ => text,
+    }
+}
+
+fn abbreviate(text: &str) -> String {
+    if let Some((code, _)) = ABBREVIATIONS.iter().find(|(_, full)| *full == text) {
+        return code.to_string();
+    }
+
+    // Escape separators, and single characters that would read as an abbreviation
+    let is_code = text.chars().count() == 1
+        && ABBREVIATIONS
+            .iter()
+            .any(|(code, _)| text.starts_with(*code));
+
+    let mut escaped = String::with_capacity(text.len() + 1);
+    for c in text.chars() {
+        if is_code || matches!(c, ':' | '=' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Five bits per character, most significant first; leftover bits are padding
+fn decode_base32(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in text.bytes() {
+        let value = ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+fn encode_base32(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len() * 8 / 5 + 1);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | u32::from(byte);
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            text.push(ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+
+    if bits > 0 {
+        text.push(ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_launch_services_identifiers() {
+        let rmd = DynamicUti::decode("dyn.ah62d4rv4ge81e5pe").unwrap();
+        assert_eq!(rmd.conforms_to, ["public.data"]);
+        assert_eq!(rmd.tag("public.filename-extension"), Some("rmd"));
+        assert_eq!(
+            rmd.to_string(),
+            "public.filename-extension = rmd (conforms to public.data)"
+        );
+
+        let kdbx = DynamicUti::decode("dyn.ah62d4rv4ge8003dcta").unwrap();
+        assert_eq!(kdbx.tag("public.filename-extension"), Some("kdbx"));
+
+        assert_eq!(DynamicUti::decode("public.plain-text"), None);
+        assert_eq!(DynamicUti::decode("dyn.aiiii"), None);
+    }
+
+    #[test]
+    fn encodes_like_launch_services() {
+        let rmd = DynamicUti::for_tag("public.filename-extension", "rmd", "public.data");
+        assert_eq!(rmd.encode(), "dyn.ah62d4rv4ge81e5pe");
+        assert_eq!(
+            DynamicUti::for_tag("public.filename-extension", "kdbx", "public.data").encode(),
+            "dyn.ah62d4rv4ge8003dcta"
+        );
+
+        // Tags that need escaping survive the round trip
+        for tag in ["a:b", "x=y", "back\\slash", "7", "B", "tar.gz"] {
+            let dynamic = DynamicUti::for_tag("public.mime-type", tag, "public.text");
+            assert_eq!(DynamicUti::decode(&dynamic.encode()), Some(dynamic));
+        }
+    }
+
+    #[test]
+    fn prefers_extension_then_mime_type_for_handlers() {
+        let os_type = DynamicUti::for_tag("com.apple.ostype", "TEXT", "public.data");
+        let decoded = DynamicUti::decode(&os_type.encode()).unwrap();
+        assert_eq!(decoded.handler_tag(), None);
+
+        let mut mixed = os_type.clone();
+        mixed
+            .tags
+            .push(("public.mime-type".to_string(), "text/x-notes".to_string()));
+        assert_eq!(
+            mixed.handler_tag(),
+            Some((TagClass::MimeType, "text/x-notes"))
+        );
+
+        mixed
+            .tags
+            .push(("public.filename-extension".to_string(), "notes".to_string()));
+        assert_eq!(mixed.handler_tag(), Some((TagClass::Extension, "notes")));
+    }
+}
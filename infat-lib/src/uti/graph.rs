@@ -0,0 +1,678 @@
+//! Conformance graph of UTI declarations, usable without Launch Services
+
+use super::system::SYSTEM_TYPES;
+use super::tags::TypeTags;
+use crate::{
+    app_index::{self, AppIndex, IndexedApp},
+    discovery::SearchPath,
+    error::{InfatError, Result},
+};
+use plist::{Dictionary, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Graph of system and app-declared types, and the index it was built from
+static INSTALLED: Mutex<Option<(Arc<AppIndex>, Arc<UtiGraph>)>> = Mutex::new(None);
+
+/// A declared type: its identifier, the types it conforms to, and its tags
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeDeclaration {
+    pub identifier: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conforms_to: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mime_types: Vec<String>,
+
+    /// Classic Mac OS four-character type codes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub os_types: Vec<String>,
+
+    /// The app whose `Info.plist` declared the type, `None` for system types
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_by: Option<DeclaringApp>,
+}
+
+impl TypeDeclaration {
+    pub fn new(identifier: impl Into<String>) -> Self {
+        Self {
+            identifier: identifier.into(),
+            conforms_to: Vec::new(),
+            extensions: Vec::new(),
+            mime_types: Vec::new(),
+            os_types: Vec::new(),
+            declared_by: None,
+        }
+    }
+
+    /// The declared tags, grouped by tag class
+    pub fn tags(&self) -> TypeTags {
+        TypeTags {
+            extensions: self.extensions.clone(),
+            mime_types: self.mime_types.clone(),
+            os_types: self.os_types.clone(),
+        }
+    }
+
+    /// Parse one entry of `UTExportedTypeDeclarations` or `UTImportedTypeDeclarations`
+    pub fn from_plist(dict: &Dictionary) -> Option<Self> {
+        let identifier = dict.get("UTTypeIdentifier")?.as_string()?.trim();
+        if identifier.is_empty() {
+            return None;
+        }
+
+        let tags = dict
+            .get("UTTypeTagSpecification")
+            .and_then(Value::as_dictionary);
+        let tag = |class: &str| tags.and_then(|tags| tags.get(class)).map(strings);
+
+        Some(Self {
+            conforms_to: dict
+                .get("UTTypeConformsTo")
+                .map(strings)
+                .unwrap_or_default(),
+            extensions: tag("public.filename-extension")
+                .unwrap_or_default()
+                .into_iter()
+                .map(|ext| ext.trim_start_matches('.').to_string())
+                .collect(),
+            mime_types: tag("public.mime-type").unwrap_or_default(),
+            os_types: tag("com.apple.ostype").unwrap_or_default(),
+            ..Self::new(identifier)
+        })
+    }
+}
+
+/// Every declaration listed under `key` (e.g. `UTExportedTypeDeclarations`) in an `Info.plist`
+pub fn declarations_in(info: &Dictionary, key: &str) -> Vec<TypeDeclaration> {
+    info.get(key)
+        .and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(Value::as_dictionary)
+                .filter_map(TypeDeclaration::from_plist)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Info.plist values may hold either a single string or an array of them
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(values) => values
+            .iter()
+            .filter_map(Value::as_string)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// An installed app that declared a type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclaringApp {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+
+    pub path: PathBuf,
+
+    /// Whether the app exports (owns) the type rather than importing it
+    pub exported: bool,
+}
+
+impl DeclaringApp {
+    fn new(app: &IndexedApp, exported: bool) -> Self {
+        Self {
+            name: app.preferred_name().to_string(),
+            bundle_id: app.bundle_id.clone(),
+            path: app.path.clone(),
+            exported,
+        }
+    }
+}
+
+impl std::fmt::Display for DeclaringApp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(bundle_id) = &self.bundle_id {
+            write!(f, " ({bundle_id})")?;
+        }
+        Ok(())
+    }
+}
+
+/// A set of type declarations linked by their conformance.
+///
+/// Identifiers are compared case-insensitively, like Launch Services does.
+/// Parents that aren't declared themselves still take part in the graph, they
+/// just have no parents of their own.
+#[derive(Debug, Clone, Default)]
+pub struct UtiGraph {
+    declarations: Vec<TypeDeclaration>,
+    by_identifier: HashMap<String, usize>,
+    children: HashMap<String, Vec<usize>>,
+}
+
+impl UtiGraph {
+    /// The system types compiled into the library
+    pub fn system() -> &'static UtiGraph {
+        static SYSTEM: OnceLock<UtiGraph> = OnceLock::new();
+
+        SYSTEM.get_or_init(|| {
+            SYSTEM_TYPES
+                .iter()
+                .map(|ty| TypeDeclaration {
+                    identifier: ty.identifier.to_string(),
+                    conforms_to: ty.conforms_to.iter().map(|s| s.to_string()).collect(),
+                    extensions: ty.extensions.iter().map(|s| s.to_string()).collect(),
+                    mime_types: ty.mime_types.iter().map(|s| s.to_string()).collect(),
+                    os_types: ty.os_types.iter().map(|s| s.to_string()).collect(),
+                    declared_by: None,
+                })
+                .collect()
+        })
+    }
+
+    /// The system types plus those declared by the given apps.
+    ///
+    /// System declarations always win. Exported declarations come before imported
+    /// ones, and earlier apps before later ones, so the app that owns a type is
+    /// credited with it even when other apps import it too.
+    pub fn with_apps(apps: &[IndexedApp]) -> Self {
+        let mut graph = Self::system().clone();
+
+        for exported in [true, false] {
+            for app in apps {
+                let declarations = if exported {
+                    &app.exported_types
+                } else {
+                    &app.imported_types
+                };
+
+                for declaration in declarations {
+                    if graph.contains(&declaration.identifier) {
+                        continue;
+                    }
+
+                    graph.insert(TypeDeclaration {
+                        declared_by: Some(DeclaringApp::new(app, exported)),
+                        ..declaration.clone()
+                    });
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// The system types plus those declared by every application in the search paths
+    pub fn installed(roots: &[SearchPath]) -> Result<Arc<UtiGraph>> {
+        let index = app_index::shared(roots)?;
+        let mut installed = INSTALLED.lock().map_err(|_| InfatError::Generic {
+            message: "UTI graph lock was poisoned".to_string(),
+        })?;
+
+        // Rebuilt whenever the application index is
+        if let Some((built_from, graph)) = installed.as_ref() {
+            if Arc::ptr_eq(built_from, &index) {
+                return Ok(Arc::clone(graph));
+            }
+        }
+
+        let graph = Arc::new(Self::with_apps(index.apps()));
+        *installed = Some((index, Arc::clone(&graph)));
+        Ok(graph)
+    }
+
+    /// Add a declaration, replacing any existing one with the same identifier
+    pub fn insert(&mut self, declaration: TypeDeclaration) {
+        let key = declaration.identifier.to_lowercase();
+
+        let index = match self.by_identifier.get(&key) {
+            Some(&index) => {
+                for parent in &self.declarations[index].conforms_to {
+                    if let Some(children) = self.children.get_mut(&parent.to_lowercase()) {
+                        children.retain(|&child| child != index);
+                    }
+                }
+                self.declarations[index] = declaration;
+                index
+            }
+            None => {
+                self.declarations.push(declaration);
+                self.by_identifier.insert(key, self.declarations.len() - 1);
+                self.declarations.len() - 1
+            }
+        };
+
+        for parent in &self.declarations[index].conforms_to {
+            self.children
+                .entry(parent.to_lowercase())
+                .or_default()
+                .push(index);
+        }
+    }
+
+    /// The declaration for an identifier, if there is one
+    pub fn get(&self, identifier: &str) -> Option<&TypeDeclaration> {
+        self.by_identifier
+            .get(&identifier.to_lowercase())
+            .map(|&index| &self.declarations[index])
+    }
+
+    pub fn contains(&self, identifier: &str) -> bool {
+        self.get(identifier).is_some()
+    }
+
+    /// All declarations, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &TypeDeclaration> {
+        self.declarations.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.declarations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declarations.is_empty()
+    }
+
+    /// Every declaration claiming a filename extension
+    pub fn types_for_extension(&self, extension: &str) -> Vec<&TypeDeclaration> {
+        let extension = extension.trim_start_matches('.');
+        self.types_with_tag(extension, |declaration| &declaration.extensions)
+    }
+
+    /// The most specific declaration claiming a filename extension
+    pub fn preferred_type_for_extension(&self, extension: &str) -> Option<&TypeDeclaration> {
+        self.most_specific(self.types_for_extension(extension))
+    }
+
+    /// Every declaration claiming a MIME type
+    pub fn types_for_mime_type(&self, mime_type: &str) -> Vec<&TypeDeclaration> {
+        self.types_with_tag(mime_type, |declaration| &declaration.mime_types)
+    }
+
+    /// The most specific declaration claiming a MIME type
+    pub fn preferred_type_for_mime_type(&self, mime_type: &str) -> Option<&TypeDeclaration> {
+        self.most_specific(self.types_for_mime_type(mime_type))
+    }
+
+    /// The first MIME type declared for a UTI
+    pub fn preferred_mime_type(&self, identifier: &str) -> Option<&str> {
+        self.get(identifier)
+            .and_then(|declaration| declaration.mime_types.first())
+            .map(String::as_str)
+    }
+
+    fn types_with_tag(
+        &self,
+        value: &str,
+        tags: impl Fn(&TypeDeclaration) -> &Vec<String>,
+    ) -> Vec<&TypeDeclaration> {
+        self.declarations
+            .iter()
+            .filter(|declaration| {
+                tags(declaration)
+                    .iter()
+                    .any(|tag| tag.eq_ignore_ascii_case(value))
+            })
+            .collect()
+    }
+
+    /// The candidate conforming to the most other candidates, the first one on a tie
+    fn most_specific<'a>(
+        &self,
+        candidates: Vec<&'a TypeDeclaration>,
+    ) -> Option<&'a TypeDeclaration> {
+        candidates.iter().copied().min_by_key(|candidate| {
+            let generalizes = candidates
+                .iter()
+                .filter(|other| {
+                    other.identifier != candidate.identifier
+                        && self.conforms_to(&candidate.identifier, &other.identifier)
+                })
+                .count();
+            std::cmp::Reverse(generalizes)
+        })
+    }
+
+    /// Whether `identifier` is `parent` or (transitively) conforms to it
+    pub fn conforms_to(&self, identifier: &str, parent: &str) -> bool {
+        identifier.eq_ignore_ascii_case(parent)
+            || self
+                .ancestors(identifier)
+                .iter()
+                .any(|ancestor| ancestor.eq_ignore_ascii_case(parent))
+    }
+
+    /// Every type `identifier` conforms to, nearest first
+    pub fn ancestors(&self, identifier: &str) -> Vec<&str> {
+        self.walk(identifier, |current| {
+            self.get(current)
+                .map(|declaration| declaration.conforms_to.iter().map(String::as_str).collect())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Every declared type that (transitively) conforms to `identifier`, nearest first
+    pub fn descendants(&self, identifier: &str) -> Vec<&str> {
+        self.walk(identifier, |current| {
+            self.children
+                .get(&current.to_lowercase())
+                .map(|children| {
+                    children
+                        .iter()
+                        .map(|&index| self.declarations[index].identifier.as_str())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Breadth-first walk from `start`, excluding it, that tolerates cycles
+    fn walk<'a>(&'a self, start: &str, next: impl Fn(&str) -> Vec<&'a str>) -> Vec<&'a str> {
+        let mut seen = HashSet::from([start.to_lowercase()]);
+        let mut queue = VecDeque::from(next(start));
+        let mut found = Vec::new();
+
+        while let Some(current) = queue.pop_front() {
+            if seen.insert(current.to_lowercase()) {
+                found.push(current);
+                queue.extend(next(current));
+            }
+        }
+
+        found
+    }
+}
+
+impl FromIterator<TypeDeclaration> for UtiGraph {
+    fn from_iter<I: IntoIterator<Item = TypeDeclaration>>(iter: I) -> Self {
+        let mut graph = Self::default();
+        for declaration in iter {
+            graph.insert(declaration);
+        }
+        graph
+    }
+}
+
+/// A Uniform Type Identifier such as `public.png`.
+///
+/// Conformance is answered from the system types compiled into the library, so
+/// it works on any platform. Types declared by installed apps are only known to
+/// [`UtiGraph::installed`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Uti(String);
+
+impl Uti {
+    pub fn new(identifier: impl Into<String>) -> Self {
+        Self(identifier.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The declaration of this type, if it is known
+    pub fn declaration(&self) -> Option<&'static TypeDeclaration> {
+        UtiGraph::system().get(&self.0)
+    }
+
+    /// Whether this type is `parent` or (transitively) conforms to it
+    pub fn conforms_to(&self, parent: impl AsRef<str>) -> bool {
+        UtiGraph::system().conforms_to(&self.0, parent.as_ref())
+    }
+
+    /// Every type this one conforms to, nearest first
+    pub fn ancestors(&self) -> Vec<Uti> {
+        UtiGraph::system()
+            .ancestors(&self.0)
+            .into_iter()
+            .map(Uti::from)
+            .collect()
+    }
+
+    /// Every known type that conforms to this one, nearest first
+    pub fn descendants(&self) -> Vec<Uti> {
+        UtiGraph::system()
+            .descendants(&self.0)
+            .into_iter()
+            .map(Uti::from)
+            .collect()
+    }
+}
+
+impl AsRef<str> for Uti {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Uti {
+    fn from(identifier: String) -> Self {
+        Self(identifier)
+    }
+}
+
+impl From<&str> for Uti {
+    fn from(identifier: &str) -> Self {
+        Self(identifier.to_string())
+    }
+}
+
+impl std::fmt::Display for Uti {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uti::{SuperType, TagClass};
+
+    #[test]
+    fn covers_every_supertype() {
+        let graph = UtiGraph::system();
+        let missing: Vec<_> = SuperType::all()
+            .iter()
+            .map(SuperType::uti_string)
+            .filter(|uti| !graph.contains(uti))
+            .collect();
+
+        assert!(missing.is_empty(), "undeclared supertypes: {missing:?}");
+    }
+
+    #[test]
+    fn every_parent_is_declared() {
+        let graph = UtiGraph::system();
+        for declaration in graph.iter() {
+            for parent in &declaration.conforms_to {
+                assert!(
+                    graph.contains(parent),
+                    "{} conforms to undeclared {parent}",
+                    declaration.identifier
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn walks_conformance() {
+        let aiff = Uti::from("public.aiff-audio");
+        assert!(aiff.conforms_to("public.aifc-audio"));
+        assert!(aiff.conforms_to("PUBLIC.AUDIO"));
+        assert!(aiff.conforms_to("public.item"));
+        assert!(!aiff.conforms_to("public.movie"));
+
+        let system = UtiGraph::system();
+        assert_eq!(
+            system
+                .preferred_type_for_mime_type("audio/x-m4a")
+                .map(|d| d.identifier.as_str()),
+            Some("com.apple.m4a-audio")
+        );
+        assert_eq!(
+            system.preferred_mime_type("com.adobe.pdf"),
+            Some("application/pdf")
+        );
+        assert_eq!(aiff.ancestors()[0].as_str(), "public.aifc-audio");
+
+        let image = Uti::from("public.image");
+        let descendants = image.descendants();
+        assert!(descendants.contains(&Uti::from("public.png")));
+        assert!(descendants.contains(&Uti::from("com.adobe.raw-image")));
+        assert!(!descendants.contains(&image));
+
+        // Cycles and redeclarations don't trip the walk
+        let graph: UtiGraph = [
+            TypeDeclaration {
+                conforms_to: vec!["b".into()],
+                ..TypeDeclaration::new("a")
+            },
+            TypeDeclaration {
+                conforms_to: vec!["a".into()],
+                ..TypeDeclaration::new("b")
+            },
+            TypeDeclaration {
+                conforms_to: vec!["a".into()],
+                ..TypeDeclaration::new("c")
+            },
+            TypeDeclaration::new("c"),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(graph.ancestors("a"), ["b"]);
+        assert_eq!(graph.descendants("a"), ["b"]);
+        assert!(!graph.conforms_to("c", "a"));
+    }
+
+    #[test]
+    fn groups_tags_by_class() {
+        let mut tags = UtiGraph::system().get("public.jpeg").unwrap().tags();
+        assert_eq!(tags.extensions, ["jpg", "jpeg", "jpe"]);
+        assert_eq!(tags.os_types, ["JPEG"]);
+
+        // What Launch Services adds is merged without duplicates
+        let mut system = TypeTags::default();
+        system.extend(TagClass::Extension, ["JPG", "jfif"]);
+        system.extend(TagClass::MimeType, ["image/jpeg", "image/pjpeg"]);
+        system.extend(TagClass::OsType, ["JPEG", "jpeg"]);
+        tags.merge(&system);
+
+        assert_eq!(tags.extensions, ["jpg", "jpeg", "jpe", "jfif"]);
+        assert_eq!(tags.mime_types, ["image/jpeg", "image/pjpeg"]);
+        assert_eq!(tags.os_types, ["JPEG", "jpeg"]);
+        assert_eq!(
+            tags.groups().map(|(class, _)| class).collect::<Vec<_>>(),
+            TagClass::ALL
+        );
+        assert!(UtiGraph::system()
+            .get("public.item")
+            .unwrap()
+            .tags()
+            .is_empty());
+    }
+
+    #[test]
+    fn imports_types_declared_by_apps() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let contents = tmp.path().join("Rust Editor.app").join("Contents");
+        std::fs::create_dir_all(&contents).unwrap();
+        std::fs::write(
+            contents.join("Info.plist"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0">
+<dict>
+    <key>CFBundleIdentifier</key>
+    <string>org.example.rust-editor</string>
+    <key>UTExportedTypeDeclarations</key>
+    <array>
+        <dict>
+            <key>UTTypeIdentifier</key>
+            <string>org.rust-lang.rust-source</string>
+            <key>UTTypeConformsTo</key>
+            <array><string>public.source-code</string></array>
+            <key>UTTypeTagSpecification</key>
+            <dict>
+                <key>public.filename-extension</key>
+                <string>rs</string>
+                <key>public.mime-type</key>
+                <array><string>text/rust</string></array>
+                <key>com.apple.ostype</key>
+                <string>RUST</string>
+            </dict>
+        </dict>
+    </array>
+    <key>UTImportedTypeDeclarations</key>
+    <array>
+        <dict>
+            <key>UTTypeIdentifier</key>
+            <string>public.png</string>
+            <key>UTTypeConformsTo</key>
+            <string>public.data</string>
+        </dict>
+        <dict>
+            <key>UTTypeIdentifier</key>
+            <string>io.crates.cargo-lock</string>
+            <key>UTTypeTagSpecification</key>
+            <dict>
+                <key>public.filename-extension</key>
+                <array><string>.lock</string></array>
+            </dict>
+        </dict>
+    </array>
+</dict>
+</plist>
+"#,
+        )
+        .unwrap();
+
+        let index = AppIndex::build(&[tmp.path().to_path_buf()]);
+        let graph = UtiGraph::with_apps(index.apps());
+
+        let rust = graph.preferred_type_for_extension("RS").unwrap();
+        assert_eq!(rust.identifier, "org.rust-lang.rust-source");
+        assert_eq!(rust.mime_types, ["text/rust"]);
+        assert_eq!(rust.os_types, ["RUST"]);
+        assert!(graph.conforms_to(&rust.identifier, "public.text"));
+
+        let declared_by = rust.declared_by.as_ref().unwrap();
+        assert_eq!(declared_by.name, "Rust Editor");
+        assert_eq!(
+            declared_by.bundle_id.as_deref(),
+            Some("org.example.rust-editor")
+        );
+        assert!(declared_by.exported);
+
+        // System declarations win over imported ones
+        assert_eq!(
+            graph.get("public.png"),
+            UtiGraph::system().get("public.png")
+        );
+
+        assert_eq!(
+            graph
+                .preferred_type_for_mime_type("Text/Rust")
+                .map(|d| d.identifier.as_str()),
+            Some("org.rust-lang.rust-source")
+        );
+
+        let lock = graph.preferred_type_for_extension("lock").unwrap();
+        assert_eq!(lock.identifier, "io.crates.cargo-lock");
+        assert!(!lock.declared_by.as_ref().unwrap().exported);
+    }
+}
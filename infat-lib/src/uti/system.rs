@@ -0,0 +1,232 @@
+//! Declarations of the system UTIs infat knows about without asking macOS
+//!
+//! Each line is `identifier: [conforms-to...] ext [extensions...] mime [MIME types...]`,
+//! optionally followed by `ostype [four-character codes...]`, following Apple's
+//! `UTCoreTypes` declarations. When several types claim the same tag, the most
+//! specific one wins, so the order here doesn't matter.
+
+/// A UTI declaration compiled into the library
+pub(super) struct SystemType {
+    pub identifier: &'static str,
+    pub conforms_to: &'static [&'static str],
+    pub extensions: &'static [&'static str],
+    pub mime_types: &'static [&'static str],
+    pub os_types: &'static [&'static str],
+}
+
+macro_rules! system_types {
+    ($($id:literal : [$($parent:literal),*] ext [$($ext:literal),*] mime [$($mime:literal),*] $(ostype [$($os:literal),*])?;)*) => {
+        &[$(SystemType {
+            identifier: $id,
+            conforms_to: &[$($parent),*],
+            extensions: &[$($ext),*],
+            mime_types: &[$($mime),*],
+            os_types: &[$($($os),*)?],
+        }),*]
+    };
+}
+
+#[rustfmt::skip]
+pub(super) static SYSTEM_TYPES: &[SystemType] = system_types! {
+    // Roots
+    "public.item": [] ext [] mime [];
+    "public.content": [] ext [] mime [];
+    "public.composite-content": ["public.content"] ext [] mime [];
+    "public.data": ["public.item"] ext [] mime ["application/octet-stream"];
+    "public.directory": ["public.item"] ext [] mime [];
+    "public.folder": ["public.directory"] ext [] mime [] ostype ["fold"];
+    "public.volume": ["public.folder"] ext [] mime [] ostype ["disk"];
+    "com.apple.mount-point": ["public.folder"] ext [] mime [];
+    "com.apple.package": ["public.directory"] ext [] mime [];
+    "com.apple.bundle": ["public.directory"] ext [] mime [];
+    "public.symlink": ["public.item"] ext [] mime [];
+    "com.apple.alias-file": ["public.data"] ext [] mime [];
+
+    // Executables
+    "public.executable": ["public.item"] ext [] mime [];
+    "com.apple.application": ["public.executable"] ext [] mime [];
+    "com.apple.application-bundle": ["com.apple.application", "com.apple.bundle", "com.apple.package"] ext ["app"] mime [];
+    "com.apple.application-file": ["com.apple.application", "public.data"] ext [] mime [] ostype ["APPL"];
+    "com.apple.framework": ["com.apple.bundle"] ext ["framework"] mime [];
+    "public.unix-executable": ["public.data", "public.executable"] ext [] mime [];
+    "com.microsoft.windows-executable": ["public.data", "public.executable"] ext ["exe"] mime ["application/x-msdownload"];
+    "com.apple.installer-package-archive": ["public.data"] ext ["pkg"] mime [];
+
+    // Text
+    "public.text": ["public.data", "public.content"] ext [] mime [];
+    "public.plain-text": ["public.text"] ext ["txt", "text"] mime ["text/plain"] ostype ["TEXT"];
+    "public.utf8-plain-text": ["public.plain-text"] ext [] mime [];
+    "public.utf16-plain-text": ["public.plain-text"] ext [] mime [] ostype ["utxt"];
+    "public.delimited-values-text": ["public.text"] ext [] mime [];
+    "public.comma-separated-values-text": ["public.delimited-values-text"] ext ["csv"] mime ["text/csv", "text/comma-separated-values"];
+    "public.tab-separated-values-text": ["public.delimited-values-text"] ext ["tsv"] mime ["text/tab-separated-values"];
+    "public.rtf": ["public.text"] ext ["rtf"] mime ["text/rtf", "application/rtf"] ostype ["RTF "];
+    "com.apple.rtfd": ["com.apple.package", "public.composite-content"] ext ["rtfd"] mime [];
+    "public.html": ["public.text"] ext ["html", "htm", "shtml"] mime ["text/html"] ostype ["HTML"];
+    "public.xml": ["public.text"] ext ["xml"] mime ["application/xml", "text/xml"];
+    "public.xhtml": ["public.xml"] ext ["xhtml", "xht"] mime ["application/xhtml+xml"];
+    "public.css": ["public.text"] ext ["css"] mime ["text/css"];
+    "public.json": ["public.text"] ext ["json"] mime ["application/json"];
+    "public.yaml": ["public.text"] ext ["yaml", "yml"] mime ["application/x-yaml", "application/yaml"];
+    "net.daringfireball.markdown": ["public.plain-text"] ext ["md", "markdown", "mdown"] mime ["text/markdown"];
+    "public.log": ["public.plain-text"] ext ["log"] mime [];
+    "public.patch-file": ["public.plain-text"] ext ["patch", "diff"] mime ["text/x-diff"];
+    "public.vcard": ["public.text"] ext ["vcf", "vcard"] mime ["text/vcard", "text/directory"];
+    "public.calendar-event": ["public.item"] ext [] mime [];
+    "com.apple.ical.ics": ["public.calendar-event", "public.text"] ext ["ics"] mime ["text/calendar"];
+
+    // Source code
+    "public.source-code": ["public.plain-text"] ext [] mime [];
+    "public.script": ["public.source-code"] ext [] mime [];
+    "public.shell-script": ["public.script"] ext ["sh", "command"] mime ["application/x-sh", "text/x-shellscript"];
+    "public.bash-script": ["public.shell-script"] ext ["bash"] mime [];
+    "public.zsh-script": ["public.shell-script"] ext ["zsh"] mime [];
+    "public.csh-script": ["public.shell-script"] ext ["csh"] mime [];
+    "public.ksh-script": ["public.shell-script"] ext ["ksh"] mime [];
+    "public.python-script": ["public.script"] ext ["py"] mime ["text/x-python-script", "text/x-python"];
+    "public.ruby-script": ["public.script"] ext ["rb", "rbw"] mime ["text/ruby-script", "text/x-ruby"];
+    "public.perl-script": ["public.script"] ext ["pl", "pm"] mime ["text/x-perl-script", "text/x-perl"];
+    "public.php-script": ["public.script"] ext ["php", "php3", "php4", "ph3", "ph4", "phtml"] mime ["text/php", "application/php"];
+    "com.netscape.javascript-source": ["public.script"] ext ["js", "jscript", "javascript"] mime ["text/javascript", "application/javascript"];
+    // Launch Services gives `.ts` to MPEG-2 transport streams
+    "com.microsoft.typescript": ["public.source-code"] ext [] mime ["application/typescript"];
+    "public.c-source": ["public.source-code"] ext ["c"] mime ["text/x-c"];
+    "public.c-header": ["public.source-code"] ext ["h"] mime ["text/x-chdr"];
+    "public.c-plus-plus-source": ["public.source-code"] ext ["cp", "cpp", "c++", "cc", "cxx"] mime ["text/x-c++src"];
+    "public.c-plus-plus-header": ["public.c-header"] ext ["hpp", "h++", "hxx", "hh"] mime ["text/x-c++hdr"];
+    "public.objective-c-source": ["public.source-code"] ext ["m"] mime ["text/x-objective-c"];
+    "public.objective-c-plus-plus-source": ["public.source-code"] ext ["mm"] mime [];
+    "public.swift-source": ["public.source-code"] ext ["swift"] mime ["text/x-swift"];
+    "public.assembly-source": ["public.source-code"] ext ["s", "asm"] mime ["text/x-asm"];
+    "public.make-source": ["public.script"] ext ["make", "mk"] mime ["text/x-makefile"];
+    "com.sun.java-source": ["public.source-code"] ext ["java"] mime ["text/x-java-source"];
+    "com.apple.applescript.text": ["public.script"] ext ["applescript"] mime [];
+    "com.apple.applescript.script": ["public.data"] ext ["scpt"] mime [] ostype ["osas"];
+
+    // Documents
+    "com.adobe.pdf": ["public.data", "public.composite-content"] ext ["pdf"] mime ["application/pdf"] ostype ["PDF "];
+    "public.presentation": ["public.composite-content"] ext [] mime [];
+    "public.spreadsheet": ["public.content"] ext [] mime [];
+    "com.microsoft.word.doc": ["public.data", "public.composite-content"] ext ["doc"] mime ["application/msword"];
+    "com.microsoft.excel.xls": ["public.data", "public.spreadsheet"] ext ["xls"] mime ["application/vnd.ms-excel"];
+    "com.microsoft.powerpoint.ppt": ["public.data", "public.presentation"] ext ["ppt"] mime ["application/vnd.ms-powerpoint"];
+    "org.openxmlformats.wordprocessingml.document": ["public.zip-archive", "public.composite-content"] ext ["docx"] mime ["application/vnd.openxmlformats-officedocument.wordprocessingml.document"];
+    "org.openxmlformats.spreadsheetml.sheet": ["public.zip-archive", "public.spreadsheet"] ext ["xlsx"] mime ["application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"];
+    "org.openxmlformats.presentationml.presentation": ["public.zip-archive", "public.presentation"] ext ["pptx"] mime ["application/vnd.openxmlformats-officedocument.presentationml.presentation"];
+    "com.apple.iwork.pages.pages": ["com.apple.package", "public.composite-content"] ext ["pages"] mime [];
+    "com.apple.iwork.numbers.numbers": ["com.apple.package", "public.spreadsheet"] ext ["numbers"] mime [];
+    "com.apple.iwork.keynote.key": ["com.apple.package", "public.presentation"] ext ["key"] mime [];
+    "org.idpf.epub-container": ["public.zip-archive", "public.composite-content"] ext ["epub"] mime ["application/epub+zip"];
+    "public.email-message": ["public.data"] ext ["eml"] mime ["message/rfc822"];
+    "public.database": ["public.data"] ext [] mime [];
+
+    // Images
+    "public.image": ["public.data", "public.content"] ext [] mime [];
+    "public.png": ["public.image"] ext ["png"] mime ["image/png"] ostype ["PNGf"];
+    "public.jpeg": ["public.image"] ext ["jpg", "jpeg", "jpe"] mime ["image/jpeg"] ostype ["JPEG"];
+    "com.compuserve.gif": ["public.image"] ext ["gif"] mime ["image/gif"] ostype ["GIFf"];
+    "public.tiff": ["public.image"] ext ["tif", "tiff"] mime ["image/tiff"] ostype ["TIFF"];
+    "public.svg-image": ["public.image", "public.xml"] ext ["svg", "svgz"] mime ["image/svg+xml"];
+    "org.webmproject.webp": ["public.image"] ext ["webp"] mime ["image/webp"];
+    "public.heif-standard": ["public.image"] ext [] mime [];
+    "public.heic": ["public.heif-standard"] ext ["heic"] mime ["image/heic"];
+    "public.heif": ["public.heif-standard"] ext ["heif"] mime ["image/heif"];
+    "public.avif": ["public.image"] ext ["avif"] mime ["image/avif"];
+    "public.jpeg-2000": ["public.image"] ext ["jp2", "jpf", "jpx", "j2k"] mime ["image/jp2"] ostype ["jp2 "];
+    "public.jxl": ["public.image"] ext ["jxl"] mime ["image/jxl"];
+    "com.microsoft.bmp": ["public.image"] ext ["bmp", "dib"] mime ["image/bmp"] ostype ["BMPf"];
+    "com.microsoft.ico": ["public.image"] ext ["ico"] mime ["image/vnd.microsoft.icon", "image/x-icon"] ostype ["ICO "];
+    "com.apple.icns": ["public.image"] ext ["icns"] mime [] ostype ["icns"];
+    "com.adobe.photoshop-image": ["public.image"] ext ["psd"] mime ["image/vnd.adobe.photoshop"] ostype ["8BPS"];
+    "com.ilm.openexr-image": ["public.image"] ext ["exr"] mime ["image/x-exr"];
+    "public.camera-raw-image": ["public.image"] ext [] mime [];
+    "com.adobe.raw-image": ["public.camera-raw-image"] ext ["dng"] mime ["image/x-adobe-dng"];
+
+    // Audio
+    "public.audiovisual-content": ["public.data", "public.content"] ext [] mime [];
+    "public.audio": ["public.audiovisual-content"] ext [] mime [];
+    "public.mp3": ["public.audio"] ext ["mp3"] mime ["audio/mpeg", "audio/mp3"] ostype ["MPG3"];
+    "com.microsoft.waveform-audio": ["public.audio"] ext ["wav", "wave"] mime ["audio/wav", "audio/x-wav", "audio/vnd.wave"] ostype ["WAVE"];
+    "public.aifc-audio": ["public.audio"] ext ["aifc", "aiffc"] mime ["audio/x-aifc"] ostype ["AIFC"];
+    "public.aiff-audio": ["public.aifc-audio"] ext ["aiff", "aif"] mime ["audio/aiff", "audio/x-aiff"] ostype ["AIFF"];
+    "public.midi-audio": ["public.audio"] ext ["mid", "midi", "smf", "kar"] mime ["audio/midi", "audio/x-midi"] ostype ["Midi"];
+    "public.mpeg-4-audio": ["public.mpeg-4", "public.audio"] ext ["m4b"] mime ["audio/mp4"];
+    "com.apple.m4a-audio": ["public.mpeg-4-audio"] ext ["m4a"] mime ["audio/x-m4a"];
+    "com.apple.protected-mpeg-4-audio": ["public.audio"] ext ["m4p"] mime ["audio/x-m4p"];
+    "org.xiph.flac": ["public.audio"] ext ["flac"] mime ["audio/flac", "audio/x-flac"];
+    "org.xiph.ogg-audio": ["public.audio"] ext ["oga", "ogg"] mime ["audio/ogg"];
+    "public.ac3-audio": ["public.audio"] ext ["ac3"] mime ["audio/ac3"];
+    "public.aac-audio": ["public.audio"] ext ["aac", "adts"] mime ["audio/aac", "audio/x-aac"];
+    "com.real.realaudio": ["public.audio"] ext ["ra", "ram"] mime ["audio/vnd.rn-realaudio", "audio/x-pn-realaudio"];
+    "public.playlist": ["public.data", "public.content"] ext [] mime [];
+    "public.m3u-playlist": ["public.playlist", "public.text"] ext ["m3u", "m3u8"] mime ["audio/mpegurl", "audio/x-mpegurl", "application/vnd.apple.mpegurl"];
+
+    // Video
+    "public.movie": ["public.audiovisual-content"] ext [] mime [];
+    "public.video": ["public.movie"] ext [] mime [];
+    "com.apple.quicktime-movie": ["public.movie"] ext ["mov", "qt"] mime ["video/quicktime"] ostype ["MooV"];
+    "public.mpeg-4": ["public.movie"] ext ["mp4", "mpeg4"] mime ["video/mp4"] ostype ["mpg4"];
+    "com.apple.m4v-video": ["public.mpeg-4"] ext ["m4v"] mime ["video/x-m4v"];
+    "com.apple.protected-mpeg-4-video": ["com.apple.m4v-video"] ext [] mime [];
+    "public.mpeg": ["public.movie"] ext ["mpg", "mpeg", "mpe", "m75", "m15"] mime ["video/mpeg"] ostype ["MPG "];
+    "public.mpeg-2-video": ["public.movie"] ext ["m2v"] mime ["video/mpeg2"];
+    "public.mpeg-2-transport-stream": ["public.movie"] ext ["ts", "mts", "m2ts"] mime ["video/mp2t"];
+    "public.avi": ["public.movie"] ext ["avi", "vfw"] mime ["video/avi", "video/x-msvideo"] ostype ["VfW "];
+    "public.dv-movie": ["public.movie"] ext ["dv", "dif"] mime ["video/x-dv"];
+    "public.3gpp": ["public.movie"] ext ["3gp", "3gpp"] mime ["video/3gpp"];
+    "com.microsoft.windows-media-wmv": ["public.movie"] ext ["wmv"] mime ["video/x-ms-wmv"];
+    "com.real.realmedia": ["public.movie"] ext ["rm"] mime ["application/vnd.rn-realmedia"];
+    "org.webmproject.webm": ["public.movie"] ext ["webm"] mime ["video/webm"];
+    "org.matroska.mkv": ["public.movie"] ext ["mkv"] mime ["video/x-matroska"];
+
+    // Archives
+    "public.archive": ["public.data"] ext [] mime [];
+    "public.zip-archive": ["public.archive"] ext ["zip"] mime ["application/zip"] ostype ["ZIP "];
+    "com.pkware.zip-archive": ["public.zip-archive"] ext [] mime [];
+    "org.gnu.gnu-zip-archive": ["public.archive"] ext ["gz", "gzip"] mime ["application/gzip", "application/x-gzip"];
+    "org.gnu.gnu-zip-tar-archive": ["org.gnu.gnu-zip-archive"] ext ["tgz"] mime ["application/x-compressed-tar"];
+    "public.tar-archive": ["public.archive"] ext ["tar"] mime ["application/x-tar"];
+    "public.bzip2-archive": ["public.archive"] ext ["bz2", "bz"] mime ["application/x-bzip2"];
+    "org.tukaani.xz-archive": ["public.archive"] ext ["xz"] mime ["application/x-xz"];
+    "org.7-zip.7-zip-archive": ["public.archive"] ext ["7z"] mime ["application/x-7z-compressed"];
+    "com.rarlab.rar-archive": ["public.archive"] ext ["rar"] mime ["application/vnd.rar", "application/x-rar-compressed"];
+    "com.apple.archive": ["public.archive"] ext ["aar"] mime [];
+    "com.apple.xar-archive": ["public.archive"] ext ["xar"] mime ["application/x-xar"];
+    "public.disk-image": ["public.archive"] ext [] mime [];
+    "com.apple.disk-image-udif": ["public.disk-image"] ext ["dmg"] mime ["application/x-apple-diskimage"];
+    "public.iso-image": ["public.disk-image"] ext ["iso", "cdr"] mime ["application/x-iso9660-image"];
+
+    // 3D content
+    "public.3d-content": ["public.content"] ext [] mime [];
+    "com.pixar.universal-scene-description": ["public.3d-content", "public.data"] ext ["usd", "usda", "usdc"] mime ["model/vnd.usd"];
+    "com.pixar.universal-scene-description-mobile": ["public.3d-content", "public.zip-archive"] ext ["usdz"] mime ["model/vnd.usdz+zip"];
+    "com.apple.reality": ["public.3d-content", "public.data"] ext ["reality"] mime [];
+    "com.apple.scenekit.scene": ["public.3d-content", "public.data"] ext ["scn"] mime [];
+
+    // Fonts
+    "public.font": ["public.data", "public.content"] ext [] mime [];
+    "public.truetype-ttf-font": ["public.font"] ext ["ttf"] mime ["font/ttf"] ostype ["tfil"];
+    "public.opentype-font": ["public.font"] ext ["otf"] mime ["font/otf"] ostype ["OTTO"];
+    "public.truetype-collection-font": ["public.font"] ext ["ttc"] mime ["font/collection"];
+
+    // Security
+    "public.x509-certificate": ["public.data"] ext ["cer", "crt", "der"] mime ["application/x-x509-ca-cert", "application/pkix-cert"];
+    "com.rsa.pkcs-12": ["public.data"] ext ["p12", "pfx"] mime ["application/x-pkcs12"];
+
+    // URLs and bookmarks
+    "public.url": ["public.data"] ext [] mime [];
+    "public.file-url": ["public.url"] ext [] mime [];
+    "com.apple.bookmark": ["public.data"] ext [] mime [];
+    "public.bookmark": ["public.data"] ext [] mime [];
+    "com.apple.web-internet-location": ["public.data"] ext ["webloc"] mime [];
+    "com.microsoft.internet-shortcut": ["public.data"] ext ["url"] mime [];
+
+    // Property lists
+    "com.apple.property-list": ["public.data"] ext [] mime [];
+    "com.apple.xml-property-list": ["com.apple.property-list", "public.xml"] ext ["plist"] mime [];
+    "com.apple.binary-property-list": ["com.apple.property-list"] ext [] mime [];
+
+    // Launch Services pseudo-types for the default browser and mail client
+    "com.apple.default-app.web-browser": [] ext [] mime [];
+    "com.apple.default-app.mail-client": [] ext [] mime [];
+};
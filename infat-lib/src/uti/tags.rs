@@ -0,0 +1,102 @@
+//! Tags (extensions, MIME types, OSTypes) that map to a UTI
+
+use serde::Serialize;
+
+/// The kinds of tag a type can be identified by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagClass {
+    Extension,
+    MimeType,
+    OsType,
+}
+
+impl TagClass {
+    pub const ALL: [TagClass; 3] = [Self::Extension, Self::MimeType, Self::OsType];
+
+    /// The Launch Services tag class identifier
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            Self::Extension => "public.filename-extension",
+            Self::MimeType => "public.mime-type",
+            Self::OsType => "com.apple.ostype",
+        }
+    }
+}
+
+impl std::fmt::Display for TagClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Extension => write!(f, "Extensions"),
+            Self::MimeType => write!(f, "MIME types"),
+            Self::OsType => write!(f, "OSTypes"),
+        }
+    }
+}
+
+/// Every tag that maps to a type, grouped by tag class
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TypeTags {
+    pub extensions: Vec<String>,
+    pub mime_types: Vec<String>,
+    pub os_types: Vec<String>,
+}
+
+impl TypeTags {
+    pub fn get(&self, class: TagClass) -> &[String] {
+        match class {
+            TagClass::Extension => &self.extensions,
+            TagClass::MimeType => &self.mime_types,
+            TagClass::OsType => &self.os_types,
+        }
+    }
+
+    fn get_mut(&mut self, class: TagClass) -> &mut Vec<String> {
+        match class {
+            TagClass::Extension => &mut self.extensions,
+            TagClass::MimeType => &mut self.mime_types,
+            TagClass::OsType => &mut self.os_types,
+        }
+    }
+
+    /// Add tags of one class, skipping ones already present.
+    ///
+    /// Extensions and MIME types are compared case-insensitively; OSTypes are
+    /// four-character codes where case matters.
+    pub fn extend<I, S>(&mut self, class: TagClass, tags: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let existing = self.get_mut(class);
+        for tag in tags {
+            let tag = tag.into();
+            let duplicate = existing.iter().any(|known| match class {
+                TagClass::OsType => *known == tag,
+                _ => known.eq_ignore_ascii_case(&tag),
+            });
+            if !duplicate {
+                existing.push(tag);
+            }
+        }
+    }
+
+    /// Add every tag from another set
+    pub fn merge(&mut self, other: &TypeTags) {
+        for class in TagClass::ALL {
+            self.extend(class, other.get(class).iter().cloned());
+        }
+    }
+
+    /// The non-empty tag classes, in a stable order
+    pub fn groups(&self) -> impl Iterator<Item = (TagClass, &[String])> {
+        TagClass::ALL
+            .into_iter()
+            .map(|class| (class, self.get(class)))
+            .filter(|(_, tags)| !tags.is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups().next().is_none()
+    }
+}
@@ -0,0 +1,601 @@
+//! Watch mode: keep the system's handlers in line with the configuration
+//!
+//! The watcher waits for the config file or the Launch Services database to
+//! change, debounces bursts of events, and re-applies whatever has drifted.
+//! Both where change events come from ([`ChangeSource`]) and how handlers are
+//! read and written ([`HandlerStore`]) are traits, so the loop can be driven by
+//! real files or by in-memory fakes.
+
+use crate::{
+    config::{Config, ConfigEntry, EntryKind},
+    error::Result,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, info, warn};
+
+/// Where change notifications come from
+pub trait ChangeSource {
+    /// Wait for watched paths to change, for at most `timeout` (or forever when `None`).
+    ///
+    /// Returns the paths that changed, an empty list on timeout, or `None` once the
+    /// source is closed and no more changes will arrive.
+    fn wait(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<PathBuf>>>;
+}
+
+/// How the watcher reads and writes handlers
+pub trait HandlerStore {
+    /// The bundle ID the entry's app resolves to
+    fn expected_handler(&mut self, config: &Config, entry: &ConfigEntry) -> Result<String>;
+
+    /// The bundle ID currently handling the entry
+    fn current_handler(&mut self, config: &Config, entry: &ConfigEntry) -> Result<Option<String>>;
+
+    /// Make the entry's app the handler
+    fn apply(&mut self, config: &Config, entry: &ConfigEntry) -> Result<()>;
+}
+
+/// Watches files by polling their modification time and size
+#[derive(Debug)]
+pub struct PollingSource {
+    files: Vec<(PathBuf, Option<FileStamp>)>,
+    interval: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Option<Self> {
+        fs::metadata(path).ok().map(|meta| Self {
+            modified: meta.modified().ok(),
+            len: meta.len(),
+        })
+    }
+}
+
+impl PollingSource {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>, interval: Duration) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = FileStamp::read(&path);
+                (path, stamp)
+            })
+            .collect();
+
+        Self { files, interval }
+    }
+
+    fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+
+        for (path, stamp) in &mut self.files {
+            let current = FileStamp::read(path);
+            if current != *stamp {
+                *stamp = current;
+                changed.push(path.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+impl ChangeSource for PollingSource {
+    fn wait(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<PathBuf>>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            let changed = self.changed();
+            if !changed.is_empty() {
+                return Ok(Some(changed));
+            }
+
+            let sleep = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Ok(Some(Vec::new()));
+                    }
+                    remaining.min(self.interval)
+                }
+                None => self.interval,
+            };
+
+            thread::sleep(sleep);
+        }
+    }
+}
+
+/// Receives change notifications sent through a [`MemoryTrigger`]
+#[derive(Debug)]
+pub struct MemorySource {
+    receiver: Receiver<PathBuf>,
+}
+
+/// Sends change notifications to a [`MemorySource`]
+#[derive(Debug, Clone)]
+pub struct MemoryTrigger {
+    sender: Sender<PathBuf>,
+}
+
+impl MemoryTrigger {
+    /// Report that `path` changed
+    pub fn touch(&self, path: impl Into<PathBuf>) {
+        // The watcher may already be gone, in which case nobody cares
+        let _ = self.sender.send(path.into());
+    }
+}
+
+/// Create a connected in-memory change source and trigger
+pub fn memory_source() -> (MemoryTrigger, MemorySource) {
+    let (sender, receiver) = mpsc::channel();
+    (MemoryTrigger { sender }, MemorySource { receiver })
+}
+
+impl ChangeSource for MemorySource {
+    fn wait(&mut self, timeout: Option<Duration>) -> Result<Option<Vec<PathBuf>>> {
+        let first = match timeout {
+            Some(timeout) => match self.receiver.recv_timeout(timeout) {
+                Ok(path) => path,
+                Err(RecvTimeoutError::Timeout) => return Ok(Some(Vec::new())),
+                Err(RecvTimeoutError::Disconnected) => return Ok(None),
+            },
+            None => match self.receiver.recv() {
+                Ok(path) => path,
+                Err(_) => return Ok(None),
+            },
+        };
+
+        let mut changed = vec![first];
+        changed.extend(self.receiver.try_iter());
+
+        Ok(Some(changed))
+    }
+}
+
+/// Options for the watch loop
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    /// How long events must stop arriving before the watcher acts on them
+    pub debounce: Duration,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+        }
+    }
+}
+
+/// A handler that was changed back to the configured app
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reversion {
+    pub kind: EntryKind,
+    pub key: String,
+    /// The user-defined supertype or `recursive` entry the entry was expanded from
+    pub supertype: Option<String>,
+    pub app: String,
+    /// Bundle ID of the app that had taken over, if any
+    pub previous: Option<String>,
+}
+
+/// Keeps handlers matching a configuration file
+pub struct Watcher<S, H> {
+    config_path: PathBuf,
+    config: Config,
+    source: S,
+    store: H,
+    options: WatchOptions,
+    /// `enforce = false` entries that have already been applied, and the app they were applied with
+    applied_once: HashMap<(EntryKind, String), String>,
+}
+
+impl<S: ChangeSource, H: HandlerStore> Watcher<S, H> {
+    /// Create a watcher, loading the configuration from `config_path`
+    pub fn new(
+        config_path: impl Into<PathBuf>,
+        source: S,
+        store: H,
+        options: WatchOptions,
+    ) -> Result<Self> {
+        let config_path = config_path.into();
+        let config = Config::from_file(&config_path)?;
+        config.validate()?;
+
+        Ok(Self::with_config(
+            config_path,
+            config,
+            source,
+            store,
+            options,
+        ))
+    }
+
+    /// Create a watcher for an already loaded configuration
+    pub fn with_config(
+        config_path: impl Into<PathBuf>,
+        config: Config,
+        source: S,
+        store: H,
+        options: WatchOptions,
+    ) -> Self {
+        Self {
+            config_path: config_path.into(),
+            config,
+            source,
+            store,
+            options,
+            applied_once: HashMap::new(),
+        }
+    }
+
+    /// The configuration currently being enforced
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Bring every entry in line with the configuration.
+    ///
+    /// Enforced entries are re-applied whenever their handler differs; entries with
+    /// `enforce = false` are applied the first time they are seen (or when their app
+    /// changes in the config) and left alone afterwards.
+    pub fn sync(&mut self) -> Vec<Reversion> {
+        let mut reverted = Vec::new();
+
+        for entry in self.config.entries() {
+            let once_key = (entry.kind, entry.key.to_string());
+
+            if !entry.binding.enforce {
+                if self.applied_once.get(&once_key) == Some(&entry.binding.app) {
+                    continue;
+                }
+
+                match self.store.apply(&self.config, &entry) {
+                    Ok(()) => {
+                        info!("Applied {} → {} (not enforced)", entry, entry.binding);
+                        self.applied_once
+                            .insert(once_key, entry.binding.app.clone());
+                    }
+                    Err(e) => warn!("Failed to apply {} → {}: {}", entry, entry.binding, e),
+                }
+                continue;
+            }
+
+            match check_and_revert(&mut self.store, &self.config, &entry) {
+                Ok(Some(reversion)) => reverted.push(reversion),
+                Ok(None) => {}
+                Err(e) => warn!("Could not enforce {} → {}: {}", entry, entry.binding, e),
+            }
+        }
+
+        reverted
+    }
+
+    /// Wait for the next batch of changes, merging events that arrive within the debounce window
+    pub fn next_changes(&mut self) -> Result<Option<Vec<PathBuf>>> {
+        let Some(mut changed) = self.source.wait(None)? else {
+            return Ok(None);
+        };
+
+        while let Some(more) = self.source.wait(Some(self.options.debounce))? {
+            if more.is_empty() {
+                break;
+            }
+            changed.extend(more);
+        }
+
+        changed.sort();
+        changed.dedup();
+        Ok(Some(changed))
+    }
+
+    /// React to a batch of changed paths, reloading the config if it was among them
+    pub fn handle_changes(&mut self, changed: &[PathBuf]) -> Vec<Reversion> {
+        if changed.iter().any(|path| path == &self.config_path) {
+            self.reload();
+        }
+
+        self.sync()
+    }
+
+    /// Sync once, then keep syncing after every change until the source closes
+    pub fn run(&mut self, mut on_revert: impl FnMut(&Reversion)) -> Result<()> {
+        self.sync().iter().for_each(&mut on_revert);
+
+        while let Some(changed) = self.next_changes()? {
+            debug!("Detected changes in {:?}", changed);
+            self.handle_changes(&changed)
+                .iter()
+                .for_each(&mut on_revert);
+        }
+
+        Ok(())
+    }
+
+    fn reload(&mut self) {
+        info!(
+            "Configuration changed, reloading {}",
+            self.config_path.display()
+        );
+
+        match Config::from_file(&self.config_path).and_then(|config| {
+            config.validate()?;
+            Ok(config)
+        }) {
+            Ok(config) => {
+                self.config = config;
+            }
+            // Keep enforcing the last good configuration rather than nothing at all
+            Err(e) => warn!("Keeping previous configuration, reload failed: {}", e),
+        }
+    }
+}
+
+fn check_and_revert<H: HandlerStore>(
+    store: &mut H,
+    config: &Config,
+    entry: &ConfigEntry,
+) -> Result<Option<Reversion>> {
+    let expected = store.expected_handler(config, entry)?;
+    let current = store.current_handler(config, entry)?;
+
+    // Launch Services compares bundle identifiers case-insensitively
+    if current
+        .as_deref()
+        .is_some_and(|current| current.eq_ignore_ascii_case(&expected))
+    {
+        return Ok(None);
+    }
+
+    store.apply(config, entry)?;
+
+    info!(
+        "Reverted {}: {} → {}",
+        entry,
+        current.as_deref().unwrap_or("None"),
+        entry.binding
+    );
+
+    Ok(Some(Reversion {
+        kind: entry.kind,
+        key: entry.key.to_string(),
+        supertype: entry.supertype.map(str::to_string),
+        app: entry.binding.app.clone(),
+        previous: current,
+    }))
+}
+
+/// Reads and writes the real Launch Services handlers
+#[cfg(target_os = "macos")]
+#[derive(Debug, Default)]
+pub struct LaunchServicesStore {
+    /// How re-applied entries treat an app that doesn't declare what it's set for
+    pub enforcement: crate::capability::Enforcement,
+}
+
+#[cfg(target_os = "macos")]
+impl LaunchServicesStore {
+    fn target(config: &Config, entry: &ConfigEntry) -> Result<crate::association::HandlerTarget> {
+        use crate::association;
+
+        match entry.kind {
+            EntryKind::Type => association::type_target(&entry.key, config),
+            EntryKind::Mime => association::mime_target(&entry.key, config),
+            EntryKind::Extension => association::extension_target(&entry.key, config),
+            EntryKind::File => association::file_target(&entry.key, config),
+            EntryKind::Scheme => Ok(association::scheme_target(&entry.key)),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl HandlerStore for LaunchServicesStore {
+    fn expected_handler(&mut self, config: &Config, entry: &ConfigEntry) -> Result<String> {
+        crate::macos::workspace::resolve_to_bundle_id(&entry.binding.app, &config.settings)
+    }
+
+    fn current_handler(&mut self, config: &Config, entry: &ConfigEntry) -> Result<Option<String>> {
+        Self::target(config, entry)?.current_handler()
+    }
+
+    fn apply(&mut self, config: &Config, entry: &ConfigEntry) -> Result<()> {
+        crate::config::apply_entry(entry, config, self.enforcement)
+    }
+}
+
+/// Watch a config file and the Launch Services database, polling every `interval`
+#[cfg(target_os = "macos")]
+pub fn watch_config(
+    config_path: impl Into<PathBuf>,
+    interval: Duration,
+    options: WatchOptions,
+    enforcement: crate::capability::Enforcement,
+) -> Result<Watcher<PollingSource, LaunchServicesStore>> {
+    let config_path = config_path.into();
+    let database_path = crate::macos::launch_services_db::database_path()?;
+
+    let source = PollingSource::new([config_path.clone(), database_path], interval);
+    let store = LaunchServicesStore { enforcement };
+    Watcher::new(config_path, source, store, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Binding;
+
+    /// Handlers kept in a map, with app names doubling as bundle IDs
+    #[derive(Default)]
+    struct FakeStore {
+        handlers: HashMap<(EntryKind, String), String>,
+        applied: Vec<String>,
+    }
+
+    impl HandlerStore for FakeStore {
+        fn expected_handler(&mut self, _config: &Config, entry: &ConfigEntry) -> Result<String> {
+            Ok(entry.binding.app.clone())
+        }
+
+        fn current_handler(
+            &mut self,
+            _config: &Config,
+            entry: &ConfigEntry,
+        ) -> Result<Option<String>> {
+            Ok(self
+                .handlers
+                .get(&(entry.kind, entry.key.to_string()))
+                .cloned())
+        }
+
+        fn apply(&mut self, _config: &Config, entry: &ConfigEntry) -> Result<()> {
+            self.applied.push(entry.key.to_string());
+            self.handlers.insert(
+                (entry.kind, entry.key.to_string()),
+                entry.binding.app.clone(),
+            );
+            Ok(())
+        }
+    }
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        config.extensions.insert("md".into(), "TextEdit".into());
+        config.schemes.insert(
+            "mailto".into(),
+            Binding {
+                app: "Mail".into(),
+                enforce: false,
+                recursive: false,
+            },
+        );
+        config
+    }
+
+    fn watcher() -> (MemoryTrigger, Watcher<MemorySource, FakeStore>) {
+        let (trigger, source) = memory_source();
+        let options = WatchOptions {
+            debounce: Duration::from_millis(20),
+        };
+        let watcher = Watcher::with_config(
+            "config.toml",
+            config(),
+            source,
+            FakeStore::default(),
+            options,
+        );
+        (trigger, watcher)
+    }
+
+    #[test]
+    fn reverts_drifted_entries_only_when_enforced() {
+        let (_trigger, mut watcher) = watcher();
+
+        let reverted = watcher.sync();
+        assert_eq!(reverted.len(), 1);
+        assert_eq!(reverted[0].key, "md");
+        assert_eq!(reverted[0].previous, None);
+        assert_eq!(watcher.store.applied, ["md", "mailto"]);
+
+        // Another app steals both associations
+        for key in ["md", "mailto"] {
+            let kind = if key == "md" {
+                EntryKind::Extension
+            } else {
+                EntryKind::Scheme
+            };
+            watcher
+                .store
+                .handlers
+                .insert((kind, key.to_string()), "Thief".into());
+        }
+
+        let reverted = watcher.sync();
+        assert_eq!(
+            reverted,
+            [Reversion {
+                kind: EntryKind::Extension,
+                key: "md".into(),
+                supertype: None,
+                app: "TextEdit".into(),
+                previous: Some("Thief".into()),
+            }]
+        );
+        assert_eq!(watcher.store.applied, ["md", "mailto", "md"]);
+    }
+
+    #[test]
+    fn reversions_keep_the_supertype_they_were_expanded_from() {
+        let mut config = Config::default();
+        config.supertypes.insert(
+            "terraform".into(),
+            crate::config::SupertypeDefinition {
+                utis: vec!["com.hashicorp.terraform".into()],
+                extensions: Vec::new(),
+            },
+        );
+        config.types.insert("terraform".into(), "Zed".into());
+
+        let (_trigger, source) = memory_source();
+        let mut watcher = Watcher::with_config(
+            "config.toml",
+            config,
+            source,
+            FakeStore::default(),
+            WatchOptions::default(),
+        );
+
+        let reverted = watcher.sync();
+        assert_eq!(reverted.len(), 1);
+        assert_eq!(reverted[0].key, "com.hashicorp.terraform");
+        assert_eq!(reverted[0].supertype.as_deref(), Some("terraform"));
+    }
+
+    #[test]
+    fn debounces_bursts_of_changes() {
+        let (trigger, mut watcher) = watcher();
+
+        trigger.touch("a");
+        trigger.touch("b");
+        let burst = trigger.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            burst.touch("a");
+        });
+
+        let changed = watcher.next_changes().unwrap().unwrap();
+        handle.join().unwrap();
+        assert_eq!(changed, [PathBuf::from("a"), PathBuf::from("b")]);
+
+        drop(trigger);
+        assert_eq!(watcher.next_changes().unwrap(), None);
+    }
+
+    #[test]
+    fn polling_source_reports_modified_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[extensions]\n").unwrap();
+
+        let mut source = PollingSource::new([path.clone()], Duration::from_millis(5));
+        assert_eq!(
+            source.wait(Some(Duration::from_millis(20))).unwrap(),
+            Some(Vec::new())
+        );
+
+        fs::write(&path, "[extensions]\nmd = \"TextEdit\"\n").unwrap();
+        assert_eq!(
+            source.wait(Some(Duration::from_millis(200))).unwrap(),
+            Some(vec![path])
+        );
+    }
+}